@@ -0,0 +1,29 @@
+use bencher::{ benchmark_group, benchmark_main, Bencher };
+use adventofcode2019::intcode_8086::{ Intcode8086, RunOutcome };
+
+fn boost_program() -> Vec<i64> {
+  include_str!("../src/day9/input.txt")
+    .split(',')
+    .map(|s| s.trim().parse::<i64>().unwrap())
+    .collect()
+}
+
+fn run_boost(program: &[i64], mode: i64) -> u64 {
+  let mut cpu = Intcode8086::initialize(program.to_vec());
+  cpu.get_input_port().send(mode).expect("Send should succeed");
+  assert_eq!(cpu.run(), RunOutcome::Halted);
+  cpu.instructions_executed()
+}
+
+fn boost_test_mode(b: &mut Bencher) {
+  let program = boost_program();
+  b.iter(|| run_boost(&program, 1));
+}
+
+fn boost_sensor_mode(b: &mut Bencher) {
+  let program = boost_program();
+  b.iter(|| run_boost(&program, 2));
+}
+
+benchmark_group!(boost, boost_test_mode, boost_sensor_mode);
+benchmark_main!(boost);
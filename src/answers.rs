@@ -0,0 +1,357 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::{ Path, PathBuf };
+use super::inputhandling::{ input_root, InputPathError };
+
+pub const ANSWERS_FILE: &str = "answers.toml";
+
+// Kept next to the puzzle inputs, since the answers belong to those particular inputs.
+pub fn answers_path() -> PathBuf {
+  input_root().join(ANSWERS_FILE)
+}
+
+// The known good answer for each day and part, stored as a small TOML file:
+//
+//     [day6]
+//     part1 = "42"
+//     part2 = "4"
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Answers {
+  expected: BTreeMap<(u8, u8), String>
+}
+
+impl Answers {
+  pub fn new() -> Answers {
+    Answers::default()
+  }
+
+  pub fn load<P: AsRef<Path>>(path: P) -> Result<Answers, Box<dyn Error>> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path).map_err(|source| InputPathError { path: path.to_path_buf(), source })?;
+
+    Answers::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e).into())
+  }
+
+  pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+    std::fs::write(path, self.to_string())
+  }
+
+  pub fn parse(contents: &str) -> Result<Answers, Box<dyn Error>> {
+    let mut answers = Answers::new();
+    let mut day = None;
+
+    for (number, line) in contents.lines().enumerate().map(|(n, l)| (n + 1, l.trim())) {
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+
+      if line.starts_with('[') && line.ends_with(']') {
+        let table = &line[1..line.len() - 1];
+        day = Some(table.strip_prefix("day").and_then(|d| d.parse::<u8>().ok())
+          .ok_or_else(|| format!("line {}: expected a [dayN] table but found [{}]", number, table))?);
+        continue;
+      }
+
+      let (key, value) = match line.find('=') {
+        Some(index) => (line[..index].trim(), line[index + 1..].trim()),
+        None => return Err(format!("line {}: expected 'partN = answer' but found '{}'", number, line).into())
+      };
+
+      let part = match key {
+        "part1" => 1,
+        "part2" => 2,
+        other => return Err(format!("line {}: unknown key '{}'", number, other).into())
+      };
+
+      let day = day.ok_or_else(|| format!("line {}: '{}' appears before any [dayN] table", number, key))?;
+      let answer = unquote(value).ok_or_else(|| format!("line {}: can't read the value {}", number, value))?;
+
+      answers.set(day, part, &answer);
+    }
+
+    Ok(answers)
+  }
+
+  pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+    self.expected.get(&(day, part)).map(|s| s.as_str())
+  }
+
+  pub fn set(&mut self, day: u8, part: u8, answer: &str) {
+    self.expected.insert((day, part), answer.to_string());
+  }
+
+  pub fn entries(&self) -> impl Iterator<Item = (u8, u8, &str)> {
+    self.expected.iter().map(|((day, part), answer)| (*day, *part, answer.as_str()))
+  }
+}
+
+// Numbers may be bare; anything else is a basic TOML string with \" \\ and \n escapes.
+fn unquote(value: &str) -> Option<String> {
+  if !value.starts_with('"') {
+    return value.parse::<i64>().ok().map(|v| v.to_string());
+  }
+
+  if value.len() < 2 || !value.ends_with('"') {
+    return None;
+  }
+
+  let mut out = String::new();
+  let mut chars = value[1..value.len() - 1].chars();
+
+  while let Some(c) = chars.next() {
+    match c {
+      '\\' => match chars.next()? {
+        'n' => out.push('\n'),
+        '"' => out.push('"'),
+        '\\' => out.push('\\'),
+        _ => return None
+      },
+      '"' => return None,
+      c => out.push(c)
+    }
+  }
+
+  Some(out)
+}
+
+fn quote(value: &str) -> String {
+  format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+impl std::fmt::Display for Answers {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut previous = None;
+
+    for (day, part, answer) in self.entries() {
+      if previous != Some(day) {
+        if previous.is_some() {
+          writeln!(f)?;
+        }
+
+        writeln!(f, "[day{}]", day)?;
+        previous = Some(day);
+      }
+
+      writeln!(f, "part{} = {}", part, quote(answer))?;
+    }
+
+    Ok(())
+  }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Status {
+  Pass,
+  Fail { expected: String, actual: String },
+  Error(String),
+  // A registered day with nothing recorded for this part; it isn't run, so it neither passes
+  // nor fails.
+  Unrecorded
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Check {
+  pub day: u8,
+  pub part: u8,
+  pub status: Status
+}
+
+impl Check {
+  pub fn passed(&self) -> bool {
+    self.status == Status::Pass
+  }
+
+  pub fn failed(&self) -> bool {
+    matches!(self.status, Status::Fail { .. } | Status::Error(_))
+  }
+}
+
+impl std::fmt::Display for Check {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "day {} part {}: ", self.day, self.part)?;
+
+    match &self.status {
+      Status::Pass => write!(f, "PASS"),
+      Status::Unrecorded => write!(f, "no answer recorded, not verified"),
+      Status::Error(e) => write!(f, "ERROR {}", e),
+      Status::Fail { expected, actual } => {
+        write!(f, "FAIL")?;
+
+        let expected = expected.lines().collect::<Vec<_>>();
+        let actual = actual.lines().collect::<Vec<_>>();
+
+        if expected.len() <= 1 && actual.len() <= 1 {
+          write!(f, "\n  expected: {}", expected.first().unwrap_or(&""))?;
+          return write!(f, "\n  actual:   {}", actual.first().unwrap_or(&""));
+        }
+
+        // Multi-line answers, such as rendered letters, are compared line by line.
+        for index in 0..expected.len().max(actual.len()) {
+          match (expected.get(index), actual.get(index)) {
+            (Some(e), Some(a)) if e == a => write!(f, "\n    {}", e)?,
+            (e, a) => {
+              if let Some(e) = e {
+                write!(f, "\n  - {}", e)?;
+              }
+
+              if let Some(a) = a {
+                write!(f, "\n  + {}", a)?;
+              }
+            }
+          }
+        }
+
+        Ok(())
+      }
+    }
+  }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Verification {
+  pub checks: Vec<Check>
+}
+
+impl Verification {
+  // Unrecorded parts don't fail the run, but they don't count as passes either.
+  pub fn passed(&self) -> bool {
+    self.failures() == 0
+  }
+
+  pub fn failures(&self) -> usize {
+    self.checks.iter().filter(|c| c.failed()).count()
+  }
+
+  pub fn unrecorded(&self) -> usize {
+    self.checks.iter().filter(|c| c.status == Status::Unrecorded).count()
+  }
+}
+
+impl std::fmt::Display for Verification {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    for check in &self.checks {
+      writeln!(f, "{}", check)?;
+    }
+
+    write!(f, "{} checked, {} failed", self.checks.len() - self.unrecorded(), self.failures())?;
+
+    match self.unrecorded() {
+      0 => Ok(()),
+      unrecorded => write!(f, ", {} not recorded", unrecorded)
+    }
+  }
+}
+
+// Runs every part recorded in the answers and compares what it returns. A recorded part that
+// can't be run, say because its day has no input or isn't registered, is an error, so the
+// answers file rather than the registry decides what has to pass. Parts of the given days
+// with nothing recorded are listed as unrecorded without being run.
+pub fn verify<I, F>(answers: &Answers, days: I, mut run: F) -> Verification
+where
+  I: IntoIterator<Item = u8>,
+  F: FnMut(u8, u8) -> Result<String, Box<dyn Error>>,
+{
+  let mut checks = answers.entries().map(|(day, part, expected)| {
+    let status = match run(day, part) {
+      Err(e) => Status::Error(e.to_string()),
+      Ok(actual) if actual.trim() == expected.trim() => Status::Pass,
+      Ok(actual) => Status::Fail { expected: expected.to_string(), actual }
+    };
+
+    Check { day, part, status }
+  }).collect::<Vec<_>>();
+
+  for day in days {
+    for part in [1, 2] {
+      if answers.get(day, part).is_none() {
+        checks.push(Check { day, part, status: Status::Unrecorded });
+      }
+    }
+  }
+
+  checks.sort_by_key(|c| (c.day, c.part));
+  Verification { checks }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn answers() -> Answers {
+    Answers::parse("# known good\n[day6]\npart1 = \"42\"\npart2 = 4\n\n[day11]\npart1 = \"#..\\n.#.\"\n").unwrap()
+  }
+
+  #[test]
+  fn test_parse_and_save() {
+    let answers = answers();
+
+    assert_eq!(answers.get(6, 1), Some("42"));
+    assert_eq!(answers.get(6, 2), Some("4"));
+    assert_eq!(answers.get(11, 1), Some("#..\n.#."));
+    assert_eq!(answers.get(11, 2), None);
+
+    assert_eq!(answers.to_string(), "[day6]\npart1 = \"42\"\npart2 = \"4\"\n\n[day11]\npart1 = \"#..\\n.#.\"\n");
+    assert_eq!(Answers::parse(&answers.to_string()).unwrap(), answers);
+  }
+
+  #[test]
+  fn test_parse_errors() {
+    assert!(Answers::parse("part1 = 1").is_err());
+    assert!(Answers::parse("[six]\npart1 = 1").is_err());
+    assert!(Answers::parse("[day6]\npart3 = 1").is_err());
+    assert!(Answers::parse("[day6]\npart1 = \"open").is_err());
+    assert!(Answers::parse("[day6]\npart1 = forty").is_err());
+  }
+
+  #[test]
+  fn test_repo_answers_load() {
+    let answers = Answers::load(answers_path()).unwrap();
+    assert!(answers.entries().count() > 0);
+  }
+
+  #[test]
+  fn test_verify() {
+    let verification = verify(&answers(), vec![6, 11, 12], |day, part| {
+      match (day, part) {
+        (6, 1) => Ok("42".to_string()),
+        (6, 2) => Ok("5".to_string()),
+        (11, 1) => Ok("#..\n..#".to_string()),
+        _ => Err("unexpected part".into())
+      }
+    });
+
+    let statuses = verification.checks.iter().map(|c| (c.passed(), c.failed())).collect::<Vec<_>>();
+    assert_eq!(statuses, vec![(true, false), (false, true), (false, true), (false, false), (false, false), (false, false)]);
+    assert!(!verification.passed());
+    assert_eq!(verification.failures(), 2);
+
+    assert_eq!(verification.to_string(), [
+      "day 6 part 1: PASS",
+      "day 6 part 2: FAIL",
+      "  expected: 4",
+      "  actual:   5",
+      "day 11 part 1: FAIL",
+      "    #..",
+      "  - .#.",
+      "  + ..#",
+      "day 11 part 2: no answer recorded, not verified",
+      "day 12 part 1: no answer recorded, not verified",
+      "day 12 part 2: no answer recorded, not verified",
+      "3 checked, 2 failed, 3 not recorded",
+    ].join("\n"));
+  }
+
+  #[test]
+  fn test_verify_fails_recorded_parts_that_cant_run() {
+    let verification = verify(&answers(), Vec::new(), |day, _| Err(format!("Day {} isn't registered", day).into()));
+
+    assert_eq!(verification.checks.len(), 3);
+    assert!(verification.checks.iter().all(|c| c.failed()));
+    assert!(!verification.passed());
+    assert_eq!(verification.to_string().lines().last(), Some("3 checked, 3 failed"));
+
+    let verification = verify(&Answers::new(), vec![6], |_, _| Ok("42".to_string()));
+    assert!(verification.passed());
+    assert_eq!(verification.to_string().lines().last(), Some("0 checked, 0 failed, 2 not recorded"));
+  }
+}
@@ -0,0 +1,42 @@
+use std::io::Write;
+
+use adventofcode2019::intcode_8086::RunOutcome;
+use adventofcode2019::intcode_cli;
+
+fn main() {
+  let args : Vec<String> = std::env::args().skip(1).collect();
+  let options = match intcode_cli::parse_args(&args) {
+    Ok(Some(options)) => options,
+    Ok(None) => {
+      println!("{}", intcode_cli::USAGE);
+      return;
+    },
+    Err(e) => {
+      eprintln!("{}\n\n{}", e, intcode_cli::USAGE);
+      std::process::exit(2);
+    }
+  };
+
+  let program = intcode_cli::load_program(&options).unwrap_or_else(|e| {
+    eprintln!("{}", e);
+    std::process::exit(2);
+  });
+
+  let mut formatter = intcode_cli::OutputFormatter::new(options.mode);
+  let stdin = std::io::BufReader::new(std::io::stdin());
+
+  let execution = intcode_cli::execute(program, &options, stdin, |value| {
+    let mut stdout = std::io::stdout();
+    // A closed stdout (say, piped into head) isn't worth stopping the program for.
+    let _ = stdout.write_all(formatter.format(value).as_bytes()).and_then(|_| stdout.flush());
+  });
+
+  if let Some(profile) = &execution.profile {
+    eprint!("{}", profile);
+  }
+
+  if execution.outcome != RunOutcome::Halted {
+    eprintln!("The program {} after {} instructions", intcode_cli::describe(&execution.outcome), execution.instructions);
+    std::process::exit(1);
+  }
+}
@@ -1,6 +1,31 @@
+use std::error::Error;
+use super::inputhandling::Input;
+use super::solution::{ Answer, Solution };
+
+pub struct RocketEquation;
+
+impl Solution for RocketEquation {
+    const DAY: u8 = 1;
+    const TITLE: &'static str = "The Tyranny of the Rocket Equation";
+
+    type Parsed = Vec<u32>;
+
+    fn parse(input: &Input) -> Result<Vec<u32>, Box<dyn Error>> {
+        Ok(input.lines()?)
+    }
+
+    fn part1(masses: &Vec<u32>) -> Result<Answer, Box<dyn Error>> {
+        Ok(masses.iter().map(|m| calculate_fuels_fuel(*m)).sum::<u32>().into())
+    }
+
+    fn part2(masses: &Vec<u32>) -> Result<Answer, Box<dyn Error>> {
+        Ok(masses.iter().map(|m| calculate_needed_fuel(*m)).sum::<u32>().into())
+    }
+}
+
 pub fn calculate_needed_fuel(mass: u32) -> u32
 {
-    let mut mass_fuel = (mass / 3) - 2;
+    let mut mass_fuel = calculate_fuels_fuel(mass);
 
     let mut extra_fuel = calculate_fuels_fuel(mass_fuel);
 
@@ -47,4 +72,11 @@ mod tests {
     fn test_calculate_fuels_fuel_15_to_5() {
         assert_eq!(calculate_fuels_fuel(15), 3);
     }
+
+    #[test]
+    fn test_calculate_needed_fuel() {
+        assert_eq!(calculate_needed_fuel(14), 2);
+        assert_eq!(calculate_needed_fuel(1969), 966);
+        assert_eq!(calculate_needed_fuel(100756), 50346);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,120 @@
+use std::error::Error;
+use std::ops::RangeInclusive;
+use super::inputhandling::Input;
+use super::intcode_8086::{ Intcode8086, RunOutcome };
+use super::intcode_search::VariantSearch;
+use super::solution::{ Answer, Solution };
+
+pub const NOUN_ADDRESS: usize = 1;
+pub const VERB_ADDRESS: usize = 2;
+pub const RESULT_ADDRESS: usize = 0;
+
+pub struct ProgramAlarm;
+
+impl Solution for ProgramAlarm {
+  const DAY: u8 = 2;
+  const TITLE: &'static str = "1202 Program Alarm";
+
+  type Parsed = Vec<i64>;
+
+  fn parse(input: &Input) -> Result<Vec<i64>, Box<dyn Error>> {
+    Ok(input.csv()?)
+  }
+
+  // Restores the "1202 program alarm" state before running.
+  fn part1(program: &Vec<i64>) -> Result<Answer, Box<dyn Error>> {
+    Ok(run_with(program, 12, 2)?.into())
+  }
+
+  fn part2(program: &Vec<i64>) -> Result<Answer, Box<dyn Error>> {
+    let search = NounVerbSearch::default();
+
+    match search.find(program) {
+      Some((noun, verb)) => Ok((100 * noun + verb).into()),
+      None => Err(format!("No noun in {:?} and verb in {:?} produces {}", search.nouns, search.verbs, search.target).into())
+    }
+  }
+}
+
+// Runs a program to completion and returns its memory.
+pub fn execute(program: &[i64]) -> Result<Vec<i64>, Box<dyn Error>> {
+  run_to_halt(Intcode8086::initialize(program.to_vec()))
+}
+
+fn run_to_halt(mut cpu: Intcode8086) -> Result<Vec<i64>, Box<dyn Error>> {
+  match cpu.run() {
+    RunOutcome::Halted => Ok(cpu.memory_slice(0..cpu.memory_len())),
+    outcome => Err(format!("The program didn't halt cleanly: {:?}", outcome).into())
+  }
+}
+
+pub fn run_with(program: &[i64], noun: i64, verb: i64) -> Result<i64, Box<dyn Error>> {
+  let mut cpu = Intcode8086::initialize(program.to_vec());
+  cpu.set_memory_at(NOUN_ADDRESS, noun);
+  cpu.set_memory_at(VERB_ADDRESS, verb);
+
+  Ok(run_to_halt(cpu)?[RESULT_ADDRESS])
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct NounVerbSearch {
+  pub target: i64,
+  pub nouns: RangeInclusive<i64>,
+  pub verbs: RangeInclusive<i64>
+}
+
+impl Default for NounVerbSearch {
+  fn default() -> Self {
+    NounVerbSearch { target: 19690720, nouns: 0..=99, verbs: 0..=99 }
+  }
+}
+
+impl NounVerbSearch {
+  // Some patches send the program off into bad opcodes; those simply don't match.
+  pub fn find(&self, program: &[i64]) -> Option<(i64, i64)> {
+    let mut search = VariantSearch::new(program.to_vec());
+    search.vary_memory(NOUN_ADDRESS, self.nouns.clone());
+    search.vary_memory(VERB_ADDRESS, self.verbs.clone());
+    search.watch(RESULT_ADDRESS);
+
+    let found = search.run_until(|v| v.outcome == RunOutcome::Halted && v.memory_at(RESULT_ADDRESS) == Some(self.target))?;
+
+    Some((found.variant.patches[0].1, found.variant.patches[1].1))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_sample_programs() {
+    assert_eq!(execute(&Input::inline("1,9,10,3,2,3,11,0,99,30,40,50").csv().unwrap()).unwrap(), Input::inline("3500,9,10,70,2,3,11,0,99,30,40,50").csv().unwrap());
+    assert_eq!(execute(&Input::inline("1,0,0,0,99").csv().unwrap()).unwrap(), Input::inline("2,0,0,0,99").csv().unwrap());
+    assert_eq!(execute(&Input::inline("2,3,0,3,99").csv().unwrap()).unwrap(), Input::inline("2,3,0,6,99").csv().unwrap());
+    assert_eq!(execute(&Input::inline("2,4,4,5,99,0").csv().unwrap()).unwrap(), Input::inline("2,4,4,5,99,9801").csv().unwrap());
+    assert_eq!(execute(&Input::inline("1,1,1,4,99,5,6,0,99").csv().unwrap()).unwrap(), Input::inline("30,1,1,4,2,5,6,0,99").csv().unwrap());
+    assert_eq!(execute(&Input::inline("1002,4,3,4,33").csv().unwrap()).unwrap(), Input::inline("1002,4,3,4,99").csv().unwrap());
+    assert_eq!(execute(&Input::inline("1101,100,-1,4,0").csv().unwrap()).unwrap(), Input::inline("1101,100,-1,4,99").csv().unwrap());
+  }
+
+  #[test]
+  fn test_run_with() {
+    let program = Input::inline("1,9,10,3,2,3,11,0,99,30,40,50").csv().unwrap();
+
+    assert_eq!(run_with(&program, 9, 10).unwrap(), 3500);
+    assert_eq!(run_with(&program, 10, 9).unwrap(), 3500);
+    assert_eq!(run_with(&Input::inline("99").csv().unwrap(), 1, 2).unwrap(), 99);
+    assert!(run_with(&Input::inline("98,0,0").csv().unwrap(), 0, 0).is_err());
+  }
+
+  #[test]
+  fn test_noun_verb_search() {
+    // memory[0] = memory[noun] + memory[verb]
+    let program = Input::inline("1,0,0,0,99,7,11,13").csv().unwrap();
+    let search = NounVerbSearch { target: 24, nouns: 5..=6, verbs: 6..=7 };
+
+    assert_eq!(search.find(&program), Some((6, 7)));
+    assert_eq!(NounVerbSearch { target: 1000, ..search }.find(&program), None);
+  }
+}
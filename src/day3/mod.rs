@@ -1,5 +1,34 @@
 use std::error::Error;
 use std::collections::HashSet;
+use super::inputhandling::Input;
+use super::pattern;
+use super::solution::{ Answer, Solution };
+
+pub struct CrossedWires;
+
+impl Solution for CrossedWires {
+  const DAY: u8 = 3;
+  const TITLE: &'static str = "Crossed Wires";
+
+  type Parsed = (Vec<WiringInstruction>, Vec<WiringInstruction>);
+
+  fn parse(input: &Input) -> Result<Self::Parsed, Box<dyn Error>> {
+    let mut wires = input.csv_per_line_with(WiringInstruction::parse)?.into_iter();
+
+    match (wires.next(), wires.next(), wires.next()) {
+      (Some(one), Some(two), None) => Ok((one, two)),
+      _ => Err("Expected exactly two wires".into())
+    }
+  }
+
+  fn part1((one, two): &Self::Parsed) -> Result<Answer, Box<dyn Error>> {
+    Ok(generate_manhattan_distance(&generate_points(one), &generate_points(two)).into())
+  }
+
+  fn part2((one, two): &Self::Parsed) -> Result<Answer, Box<dyn Error>> {
+    Ok(generate_shortest_path(&generate_positions(one), &generate_positions(two)).into())
+  }
+}
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct WiringInstruction
@@ -16,26 +45,38 @@ pub enum Direction {
   Down
 }
 
+impl std::str::FromStr for Direction {
+  type Err = Box<dyn Error>;
+
+  fn from_str(value: &str) -> Result<Direction, Box<dyn Error>> {
+    match value {
+      "L" => Ok(Direction::Left),
+      "R" => Ok(Direction::Right),
+      "U" => Ok(Direction::Up),
+      "D" => Ok(Direction::Down),
+      _ => Err(format!("Bad direction '{}'", value).into())
+    }
+  }
+}
+
 impl WiringInstruction {
   pub fn parse(input: &str) -> Result<WiringInstruction, Box<dyn Error>> {
-    let dir = match input.chars().nth(0) {
-      Some('L') => Direction::Left,
-      Some('R') => Direction::Right,
-      Some('U') => Direction::Up,
-      Some('D') => Direction::Down,
-      _ => return Err(Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Bad direction")))
-    };
-
-    let run : u32 = input[1..].parse()?;
+    let (direction, run) = pattern::parse("{dir:char}{run:u32}", input)?;
 
-    Ok(WiringInstruction { direction : dir, run : run })
+    Ok(WiringInstruction { direction, run })
   }
 }
 
 #[derive(Eq, PartialEq, Debug, Hash, Copy, Clone)]
 pub struct Point {
-  x: i32,
-  y: i32
+  pub x: i32,
+  pub y: i32
+}
+
+impl Point {
+  pub fn new(x: i32, y: i32) -> Point {
+    Point { x, y }
+  }
 }
 
 
@@ -45,40 +86,40 @@ pub struct WirePosition {
   length: u32
 }
 
-pub fn generate_points(instructions: &Vec<WiringInstruction>) -> HashSet<Point> {
+pub fn generate_points(instructions: &[WiringInstruction]) -> HashSet<Point> {
   let mut set : HashSet<Point> = HashSet::new();
 
-  let mut originX = 0;
-  let mut originY = 0;
+  let mut origin_x = 0;
+  let mut origin_y = 0;
   
   for instr in instructions {
     match instr.direction {
       Direction::Left => {
-        for len in 0..instr.run
+        for _ in 0..instr.run
         {
-          originX -= 1;
-          set.insert(Point { x: originX, y: originY });
+          origin_x -= 1;
+          set.insert(Point { x: origin_x, y: origin_y });
         }
       },
       Direction::Right => {
-        for len in 0..instr.run
+        for _ in 0..instr.run
         {
-          originX += 1;
-          set.insert(Point { x: originX, y: originY });
+          origin_x += 1;
+          set.insert(Point { x: origin_x, y: origin_y });
         }
       },
       Direction::Up => {
-        for len in 0..instr.run
+        for _ in 0..instr.run
         {
-          originY += 1;
-          set.insert(Point { x: originX, y: originY });
+          origin_y += 1;
+          set.insert(Point { x: origin_x, y: origin_y });
         }
       },
       Direction::Down => {
-        for len in 0..instr.run
+        for _ in 0..instr.run
         {
-          originY -= 1;
-          set.insert(Point { x: originX, y: originY });
+          origin_y -= 1;
+          set.insert(Point { x: origin_x, y: origin_y });
         }
       }
     }
@@ -90,45 +131,45 @@ pub fn generate_points(instructions: &Vec<WiringInstruction>) -> HashSet<Point>
 }
 
 
-pub fn generate_positions(instructions: &Vec<WiringInstruction>) -> Vec<WirePosition> {
+pub fn generate_positions(instructions: &[WiringInstruction]) -> Vec<WirePosition> {
   let mut vec : Vec<WirePosition> = Vec::new();
 
-  let mut originX = 0;
-  let mut originY = 0;
-  let mut totalLen = 0;
+  let mut origin_x = 0;
+  let mut origin_y = 0;
+  let mut total_len = 0;
   
   for instr in instructions {
     match instr.direction {
       Direction::Left => {
-        for len in 0..instr.run
+        for _ in 0..instr.run
         {
-          originX -= 1;
-          totalLen += 1;
-          vec.push(WirePosition { point: Point { x: originX, y: originY }, length: totalLen });
+          origin_x -= 1;
+          total_len += 1;
+          vec.push(WirePosition { point: Point { x: origin_x, y: origin_y }, length: total_len });
         }
       },
       Direction::Right => {
-        for len in 0..instr.run
+        for _ in 0..instr.run
         {
-          originX += 1;
-          totalLen += 1;
-          vec.push(WirePosition { point: Point { x: originX, y: originY }, length: totalLen });
+          origin_x += 1;
+          total_len += 1;
+          vec.push(WirePosition { point: Point { x: origin_x, y: origin_y }, length: total_len });
         }
       },
       Direction::Up => {
-        for len in 0..instr.run
+        for _ in 0..instr.run
         {
-          originY += 1;
-          totalLen += 1;
-          vec.push(WirePosition { point: Point { x: originX, y: originY }, length: totalLen });
+          origin_y += 1;
+          total_len += 1;
+          vec.push(WirePosition { point: Point { x: origin_x, y: origin_y }, length: total_len });
         }
       },
       Direction::Down => {
-        for len in 0..instr.run
+        for _ in 0..instr.run
         {
-          originY -= 1;
-          totalLen += 1;
-          vec.push(WirePosition { point: Point { x: originX, y: originY }, length: totalLen });
+          origin_y -= 1;
+          total_len += 1;
+          vec.push(WirePosition { point: Point { x: origin_x, y: origin_y }, length: total_len });
         }
       }
     }
@@ -141,32 +182,33 @@ pub fn generate_manhattan_distance(one: &HashSet<Point>, two: &HashSet<Point>) -
   one.intersection(two).map(|pt| i32::abs(pt.x) + i32::abs(pt.y)).min().unwrap() as u32
 }
 
-pub fn generate_shortest_path(one: &Vec<WirePosition>, two: &Vec<WirePosition>) -> u32 {
-  let oneHash : HashSet<Point> = one.iter().map(|p| p.point).collect();
-  let twoHash : HashSet<Point> = two.iter().map(|p| p.point).collect();
+pub fn generate_shortest_path(one: &[WirePosition], two: &[WirePosition]) -> u32 {
+  let one_hash : HashSet<Point> = one.iter().map(|p| p.point).collect();
+  let two_hash : HashSet<Point> = two.iter().map(|p| p.point).collect();
 
-  let mut minPath = 2000000000;
+  let mut min_path = 2000000000;
 
-  for pt in oneHash.intersection(&twoHash)
+  for pt in one_hash.intersection(&two_hash)
   {
-    let oneDistance = one.iter().filter(|o| o.point == *pt).min_by(|wp, y| wp.length.cmp(&y.length));
-    let twoDistance = two.iter().filter(|o| o.point == *pt).min_by(|wp, y| wp.length.cmp(&y.length));
+    let one_distance = one.iter().filter(|o| o.point == *pt).min_by(|wp, y| wp.length.cmp(&y.length));
+    let two_distance = two.iter().filter(|o| o.point == *pt).min_by(|wp, y| wp.length.cmp(&y.length));
 
-    if let Some(oneD) = oneDistance {
-      if let Some(twoD) = twoDistance {
-        if oneD.length + twoD.length < minPath {
-          minPath = oneD.length + twoD.length;
+    if let Some(one_d) = one_distance {
+      if let Some(two_d) = two_distance {
+        if one_d.length + two_d.length < min_path {
+          min_path = one_d.length + two_d.length;
         }
       }
     }
   };
 
-  minPath
+  min_path
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::inputhandling::Input;
 
     #[test]
     fn test_parsing_instruction() {
@@ -174,17 +216,13 @@ mod tests {
       assert_eq!(WiringInstruction::parse("U1").expect("Value wasn't parsable"), WiringInstruction { direction: Direction::Up, run: 1});
       assert_eq!(WiringInstruction::parse("L48").expect("Value wasn't parsable"), WiringInstruction { direction: Direction::Left, run: 48});
       assert_eq!(WiringInstruction::parse("R37").expect("Value wasn't parsable"), WiringInstruction { direction: Direction::Right, run: 37});
+      assert!(WiringInstruction::parse("X37").is_err());
+      assert!(WiringInstruction::parse("R").is_err());
     }
 
     #[test]
     fn test_manhattan_distance() {
-      let input = "R75,D30,R83,U83,L12,D49,R71,U7,L72
-      U62,R66,U55,R34,D71,R55,D58,R83";
-
-      let directions : Vec<Vec<WiringInstruction>> = input
-      .lines()
-      .map(|s| s.trim().split(",").map(|x| WiringInstruction::parse(x).unwrap()).collect())
-      .collect();
+      let directions = Input::example(3, "example1").unwrap().csv_per_line_with(WiringInstruction::parse).unwrap();
       
       let one = generate_points(&directions[0]);
       let two = generate_points(&directions[1]);
@@ -196,13 +234,7 @@ mod tests {
 
     #[test]
     fn test_manhattan_distance2() {
-      let input = "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51
-      U98,R91,D20,R16,D67,R40,U7,R15,U6,R7";
-
-      let directions : Vec<Vec<WiringInstruction>> = input
-      .lines()
-      .map(|s| s.trim().split(",").map(|x| WiringInstruction::parse(x).unwrap()).collect())
-      .collect();
+      let directions = Input::example(3, "example2").unwrap().csv_per_line_with(WiringInstruction::parse).unwrap();
       
       let one = generate_points(&directions[0]);
       let two = generate_points(&directions[1]);
@@ -214,13 +246,7 @@ mod tests {
  
     #[test]
     fn test_steps() {
-      let input = "R75,D30,R83,U83,L12,D49,R71,U7,L72
-      U62,R66,U55,R34,D71,R55,D58,R83";
-
-      let directions : Vec<Vec<WiringInstruction>> = input
-      .lines()
-      .map(|s| s.trim().split(",").map(|x| WiringInstruction::parse(x).unwrap()).collect())
-      .collect();
+      let directions = Input::example(3, "example1").unwrap().csv_per_line_with(WiringInstruction::parse).unwrap();
       
       let one = generate_positions(&directions[0]);
       let two = generate_positions(&directions[1]);
@@ -232,13 +258,7 @@ mod tests {
     
     #[test]
     fn test_steps2() {
-      let input = "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51
-      U98,R91,D20,R16,D67,R40,U7,R15,U6,R7";
-
-      let directions : Vec<Vec<WiringInstruction>> = input
-      .lines()
-      .map(|s| s.trim().split(",").map(|x| WiringInstruction::parse(x).unwrap()).collect())
-      .collect();
+      let directions = Input::example(3, "example2").unwrap().csv_per_line_with(WiringInstruction::parse).unwrap();
       
       let one = generate_positions(&directions[0]);
       let two = generate_positions(&directions[1]);
@@ -1,4 +1,41 @@
+use std::error::Error;
+use std::ops::RangeInclusive;
 use super::fancyiters;
+use super::inputhandling::Input;
+use super::pattern;
+use super::solution::{ Answer, Solution };
+
+pub struct SecureContainer;
+
+impl Solution for SecureContainer {
+    const DAY: u8 = 4;
+    const TITLE: &'static str = "Secure Container";
+
+    type Parsed = RangeInclusive<u32>;
+
+    // The input is a single line such as "123456-654321".
+    fn parse(input: &Input) -> Result<RangeInclusive<u32>, Box<dyn Error>> {
+        let (low, high) = pattern::parse("{low:u32}-{high:u32}", input.contents().trim())?;
+        Ok(low..=high)
+    }
+
+    fn part1(range: &RangeInclusive<u32>) -> Result<Answer, Box<dyn Error>> {
+        Ok(count_passwords(range, |p| has_adjacent_pair(p) && adjacent_never_decrease(p)).into())
+    }
+
+    fn part2(range: &RangeInclusive<u32>) -> Result<Answer, Box<dyn Error>> {
+        Ok(count_passwords(range, |p| adjacent_are_same(p) && adjacent_never_decrease(p)).into())
+    }
+}
+
+fn count_passwords<F: Fn(&str) -> bool>(range: &RangeInclusive<u32>, valid: F) -> usize {
+    range.clone().map(|p| p.to_string()).filter(|p| valid(p)).count()
+}
+
+// Part 1 allows the pair to be part of a longer run; adjacent_are_same doesn't.
+pub fn has_adjacent_pair(password: &str) -> bool {
+    password.as_bytes().windows(2).any(|pair| pair[0] == pair[1])
+}
 
 pub fn adjacent_are_same(password: &str) -> bool {
     let vec = fancyiters::eliminate_runs(password.chars()).collect::<Vec<char>>();
@@ -15,12 +52,12 @@ pub fn adjacent_are_same(password: &str) -> bool {
 }
 
 pub fn adjacent_never_decrease(password: &str) -> bool {
-    let mut minVal = 0;
+    let mut min_val = 0;
 
     for character in password.chars().map(|c| c.to_digit(10).unwrap()) {
-        if minVal < character {
-            minVal = character;
-        } else if minVal > character {
+        if min_val < character {
+            min_val = character;
+        } else if min_val > character {
             return false;
         }
     }
@@ -29,9 +66,17 @@ pub fn adjacent_never_decrease(password: &str) -> bool {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_has_adjacent_pair() {
+        assert_eq!(has_adjacent_pair("111111"), true);
+        assert_eq!(has_adjacent_pair("123444"), true);
+        assert_eq!(has_adjacent_pair("123789"), false);
+    }
+
     #[test]
     fn test_adjacent_are_same() {
         assert_eq!(adjacent_are_same("122345"), true);
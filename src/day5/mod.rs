@@ -0,0 +1,108 @@
+use std::error::Error;
+use super::inputhandling::Input;
+use super::intcode_8086::RunOutcome;
+use super::intcode_transcript;
+use super::solution::{ Answer, Solution };
+
+pub const AIR_CONDITIONER: i64 = 1;
+pub const THERMAL_RADIATOR_CONTROLLER: i64 = 5;
+
+pub struct SunnyWithAsteroids;
+
+impl Solution for SunnyWithAsteroids {
+  const DAY: u8 = 5;
+  const TITLE: &'static str = "Sunny with a Chance of Asteroids";
+
+  type Parsed = Vec<i64>;
+
+  fn parse(input: &Input) -> Result<Vec<i64>, Box<dyn Error>> {
+    Ok(input.csv()?)
+  }
+
+  fn part1(program: &Vec<i64>) -> Result<Answer, Box<dyn Error>> {
+    Ok(run_diagnostic(program, AIR_CONDITIONER)?.into())
+  }
+
+  fn part2(program: &Vec<i64>) -> Result<Answer, Box<dyn Error>> {
+    Ok(run_diagnostic(program, THERMAL_RADIATOR_CONTROLLER)?.into())
+  }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum DiagnosticError {
+  Stopped { outcome: RunOutcome, outputs: Vec<i64> },
+  NoOutput,
+  // A test before the final diagnostic code reported a non-zero distance from the right answer.
+  FailedTest { index: usize, value: i64, outputs: Vec<i64> }
+}
+
+impl std::fmt::Display for DiagnosticError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      DiagnosticError::Stopped { outcome, outputs } => write!(f, "The diagnostic program stopped with {:?} after {} outputs", outcome, outputs.len()),
+      DiagnosticError::NoOutput => write!(f, "The diagnostic program produced no output"),
+      DiagnosticError::FailedTest { index, value, outputs } => write!(f, "Diagnostic test {} of {} failed with {} at output index {}", index + 1, outputs.len() - 1, value, index)
+    }
+  }
+}
+
+impl Error for DiagnosticError {}
+
+// Every output but the last is a test result, which must be 0; the last is the diagnostic code.
+pub fn check_outputs(outputs: Vec<i64>) -> Result<i64, DiagnosticError> {
+  let (code, tests) = match outputs.split_last() {
+    Some((code, tests)) => (*code, tests),
+    None => return Err(DiagnosticError::NoOutput)
+  };
+
+  match tests.iter().position(|v| *v != 0) {
+    Some(index) => Err(DiagnosticError::FailedTest { index, value: tests[index], outputs }),
+    None => Ok(code)
+  }
+}
+
+pub fn run_diagnostic(program: &[i64], system_id: i64) -> Result<i64, DiagnosticError> {
+  let outputs = intcode_transcript::run_to_outputs(program, &[system_id])
+    .map_err(|(outcome, outputs)| DiagnosticError::Stopped { outcome, outputs })?;
+
+  check_outputs(outputs)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_clean_run() {
+    assert_eq!(run_diagnostic(&Input::inline("104,0,104,0,104,4,99").csv().unwrap(), 1), Ok(4));
+    assert_eq!(run_diagnostic(&Input::inline("3,0,4,0,99").csv().unwrap(), 5), Ok(5));
+  }
+
+  #[test]
+  fn test_comparison_programs() {
+    // Outputs 999, 1000 or 1001 for an input below, equal to or above 8.
+    let program = Input::inline("3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99").csv().unwrap();
+
+    assert_eq!(run_diagnostic(&program, 7), Ok(999));
+    assert_eq!(run_diagnostic(&program, 8), Ok(1000));
+    assert_eq!(run_diagnostic(&program, 9), Ok(1001));
+  }
+
+  #[test]
+  fn test_failed_test_is_reported() {
+    let error = run_diagnostic(&Input::inline("104,0,104,3,104,0,104,42,99").csv().unwrap(), 1).unwrap_err();
+
+    assert_eq!(error, DiagnosticError::FailedTest { index: 1, value: 3, outputs: vec![0, 3, 0, 42] });
+    assert_eq!(error.to_string(), "Diagnostic test 2 of 3 failed with 3 at output index 1");
+  }
+
+  #[test]
+  fn test_bad_runs() {
+    assert_eq!(run_diagnostic(&Input::inline("99").csv().unwrap(), 1), Err(DiagnosticError::NoOutput));
+
+    match run_diagnostic(&Input::inline("104,0,3,0,3,0,99").csv().unwrap(), 1) {
+      Err(DiagnosticError::Stopped { outcome: RunOutcome::InputExhausted, outputs }) => assert_eq!(outputs, vec![0]),
+      other => panic!("Expected the run to stop on input, got {:?}", other)
+    }
+  }
+}
@@ -1,6 +1,37 @@
 use std::collections::HashMap;
 use std::error::Error;
 use petgraph::graphmap::DiGraphMap;
+use super::inputhandling::Input;
+use super::pattern;
+use super::solution::{ Answer, Solution };
+
+pub struct UniversalOrbitMap;
+
+impl Solution for UniversalOrbitMap {
+  const DAY: u8 = 6;
+  const TITLE: &'static str = "Universal Orbit Map";
+
+  type Parsed = DiGraphMap<CelestialBody, ()>;
+
+  fn parse(input: &Input) -> Result<Self::Parsed, Box<dyn Error>> {
+    Ok(generate_map(&input.lines_with(OrbitalDirection::parse)?))
+  }
+
+  fn part1(map: &Self::Parsed) -> Result<Answer, Box<dyn Error>> {
+    Ok(calculate_orbits(map).into())
+  }
+
+  fn part2(map: &Self::Parsed) -> Result<Answer, Box<dyn Error>> {
+    let santa = CelestialBody::parse("SAN")?;
+    let you = CelestialBody::parse("YOU")?;
+
+    if !map.contains_node(santa) || !map.contains_node(you) {
+      return Err("The map needs both SAN and YOU to find a transfer".into());
+    }
+
+    Ok(calculate_most_common_ancestor(&calculate_ancestors(map, santa), &calculate_ancestors(map, you)).into())
+  }
+}
 
 pub struct OrbitalDirection {
   pub orbiting_body: CelestialBody,
@@ -14,17 +45,31 @@ pub struct CelestialBody {
   pub three : char
 }
 
+impl CelestialBody {
+  pub fn parse(value: &str) -> Result<Self, Box<dyn Error>> {
+    let p = value.chars().collect::<Vec<char>>();
+
+    if p.len() != 3 {
+      return Err(format!("Body names are three characters, but '{}' has {}", value, p.len()).into());
+    }
+
+    Result::Ok(CelestialBody { one: p[0], two: p[1], three: p[2] })
+  }
+}
+
+impl std::str::FromStr for CelestialBody {
+  type Err = Box<dyn Error>;
+
+  fn from_str(value: &str) -> Result<Self, Box<dyn Error>> {
+    CelestialBody::parse(value)
+  }
+}
 
 impl OrbitalDirection {
   pub fn parse(value: &str) -> Result<Self, Box<dyn Error>> {
-    let parts = value.split(")").collect::<Vec<&str>>();
-    let p1 = parts[1].chars().collect::<Vec<char>>();
-    let p0 = parts[0].chars().collect::<Vec<char>>();
-
-    Result::Ok(OrbitalDirection {
-      orbiting_body: CelestialBody { one: p1[0], two: p1[1], three: p1[2] },
-      orbited_body: CelestialBody { one: p0[0], two: p0[1], three: p0[2] }
-    })
+    let (orbited_body, orbiting_body) = pattern::parse("{orbited}){orbiting}", value)?;
+
+    Result::Ok(OrbitalDirection { orbiting_body, orbited_body })
   }
 
   pub fn to_edge(&self) -> (CelestialBody, CelestialBody) {
@@ -32,7 +77,7 @@ impl OrbitalDirection {
   }
 }
 
-pub fn generate_map(flat_directions: &Vec<OrbitalDirection>) -> DiGraphMap<CelestialBody, ()> {
+pub fn generate_map(flat_directions: &[OrbitalDirection]) -> DiGraphMap<CelestialBody, ()> {
   let graph = DiGraphMap::from_edges(flat_directions.iter().map(|d| d.to_edge()));
   graph
 }
@@ -56,7 +101,7 @@ fn calculate_orbits_recursive(graph: &DiGraphMap<CelestialBody, ()>, from: Celes
   for n in dir {
     if (n == CelestialBody { one: 'C', two: 'O', three: 'M' }) { continue; }
 
-    orbits = calculate_orbits_recursive(&graph, n, preceeding_orbits + 1);
+    orbits = calculate_orbits_recursive(graph, n, preceeding_orbits + 1);
   }
   
   orbits
@@ -65,13 +110,13 @@ fn calculate_orbits_recursive(graph: &DiGraphMap<CelestialBody, ()>, from: Celes
 pub fn calculate_ancestors(graph: &DiGraphMap<CelestialBody, ()>, from: CelestialBody) -> HashMap<CelestialBody, u32> {
   let mut ancestors = HashMap::new();
 
-  let mut ancestor = graph.neighbors_directed(from, petgraph::Direction::Incoming).nth(0).unwrap(); // There's always one
+  let mut ancestor = graph.neighbors_directed(from, petgraph::Direction::Incoming).next().unwrap(); // There's always one
   let mut distance = 0;
 
   while (ancestor != CelestialBody { one: 'C', two: 'O', three: 'M' }) {
     ancestors.insert(ancestor, distance);
     distance += 1;
-    ancestor = graph.neighbors_directed(ancestor, petgraph::Direction::Incoming).nth(0).unwrap(); // There's always one
+    ancestor = graph.neighbors_directed(ancestor, petgraph::Direction::Incoming).next().unwrap(); // There's always one
   }
 
   ancestors
@@ -94,39 +139,27 @@ pub fn calculate_most_common_ancestor(one: &HashMap<CelestialBody, u32>, two: &H
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::inputhandling::Input;
+
+    #[test]
+    fn malformed_directions_are_errors() {
+      assert!(OrbitalDirection::parse("COM").is_err());
+      assert!(OrbitalDirection::parse("COM)").is_err());
+      assert!(OrbitalDirection::parse("CO)00B").is_err());
+      assert!(OrbitalDirection::parse("COM)00B)00C").is_err());
+      assert!(OrbitalDirection::parse("COM)00B").is_ok());
+    }
 
     #[test]
     fn given_input_part1() { 
-      let input = "COM)00B
-      00B)00C
-      00C)00D
-      00D)00E
-      00E)00F
-      00B)00G
-      00G)00H
-      00D)00I
-      00E)00J
-      00J)00K
-      00K)00L".lines().map(|l| OrbitalDirection::parse(l.trim()).unwrap()).collect();
+      let input = Input::example(6, "example1").unwrap().lines_with(OrbitalDirection::parse).unwrap();
       let map = generate_map(&input);
       assert_eq!(calculate_orbits(&map), 42);
     }
 
     #[test]
     fn given_input_part2(){
-      let input = "COM)00B
-      00B)00C
-      00C)00D
-      00D)00E
-      00E)00F
-      00B)00G
-      00G)00H
-      00D)00I
-      00E)00J
-      00J)00K
-      00K)00L
-      00K)YOU
-      00I)SAN".lines().map(|l| OrbitalDirection::parse(l.trim()).unwrap()).collect();
+      let input = Input::example(6, "example2").unwrap().lines_with(OrbitalDirection::parse).unwrap();
       let map = generate_map(&input);
       let santa_ancestors = calculate_ancestors(&map, CelestialBody { one: 'S', two: 'A', three: 'N' });
       let you_ancestors = calculate_ancestors(&map, CelestialBody { one: 'Y', two: 'O', three: 'U' });
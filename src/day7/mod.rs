@@ -1,303 +1,170 @@
-use super::intcode_8086::{Intcode8086};
+use std::error::Error;
+use std::ops::RangeInclusive;
 use bus::BusReader;
+use itertools::Itertools;
+use super::inputhandling::Input;
+use super::intcode_8086::{Intcode8086, RunOutcome};
+use super::solution::{ Answer, Solution };
+
+pub struct AmplificationCircuit;
+
+impl Solution for AmplificationCircuit {
+  const DAY: u8 = 7;
+  const TITLE: &'static str = "Amplification Circuit";
+
+  type Parsed = Vec<i64>;
+
+  fn parse(input: &Input) -> Result<Vec<i64>, Box<dyn Error>> {
+    Ok(input.csv()?)
+  }
+
+  fn part1(program: &Vec<i64>) -> Result<Answer, Box<dyn Error>> {
+    Ok(max_signal(program, 0..=4)?.into())
+  }
+
+  fn part2(program: &Vec<i64>) -> Result<Answer, Box<dyn Error>> {
+    Ok(max_signal(program, 5..=9)?.into())
+  }
+}
 
 pub struct Amplifier {
   processor: Intcode8086,
-  pub output: BusReader<i32>
+  pub output: BusReader<i64>
 }
 
 impl Amplifier {
-  pub fn create(phase_setting: u8, input_signal: i32, mut cpu: Intcode8086, mut input: BusReader<i32>) -> Amplifier {
-    let cpu_input : crossbeam_channel::Sender<i32> = cpu.get_input_port();
-    cpu_input.send(phase_setting as i32).expect("Sending a phase signal should not fail");
+  pub fn create(phase_setting: u8, input_signal: i64, mut cpu: Intcode8086, input: BusReader<i64>) -> Amplifier {
+    let cpu_input : crossbeam_channel::Sender<i64> = cpu.get_input_port();
+    cpu_input.send(phase_setting as i64).expect("Sending a phase signal should not fail");
     cpu_input.send(input_signal).expect("Sending an input signal should not fail");
 
-    std::thread::spawn(move || {
-      loop {
-        match input.recv() {
-          Ok(x) => match cpu_input.send(x) { Err(e) => break, _ => continue },
-          Err(e) => { break; }
-        };
-      }
-    });
+    Amplifier::forward(cpu_input, input);
+    Amplifier::attach(cpu)
+  }
 
-    let output = cpu.get_output_port();
+  pub fn create_no_value(phase_setting: u8, mut cpu: Intcode8086, input: BusReader<i64>) -> Amplifier {
+    let cpu_input : crossbeam_channel::Sender<i64> = cpu.get_input_port();
+    cpu_input.send(phase_setting as i64).expect("Sending a phase signal should not fail");
 
-    Amplifier {
-      processor: cpu,
-      output: output
-    }
+    Amplifier::forward(cpu_input, input);
+    Amplifier::attach(cpu)
   }
 
-  pub fn create_no_value(phase_setting: u8, mut cpu: Intcode8086, mut input: BusReader<i32>) -> Amplifier {
-    let cpu_input : crossbeam_channel::Sender<i32> = cpu.get_input_port();
-    cpu_input.send(phase_setting as i32).expect("Sending a phase signal should not fail");
-
+  // Feeds the previous amplifier's outputs in until either side goes away.
+  fn forward(cpu_input: crossbeam_channel::Sender<i64>, mut input: BusReader<i64>) {
     std::thread::spawn(move || {
-      loop {
-        match input.recv() {
-          Ok(x) => cpu_input.send(x).expect("Send shouldn't fail (nv)"),
-          Err(e) => { break; }
-        };
+      while let Ok(x) = input.recv() {
+        if cpu_input.send(x).is_err() {
+          break;
+        }
       }
     });
+  }
 
+  fn attach(mut cpu: Intcode8086) -> Amplifier {
     let output = cpu.get_output_port();
 
     Amplifier {
       processor: cpu,
-      output: output
+      output
     }
   }
-}
-
-pub fn phase_setting_generator() -> Vec<Vec<u8>> {
-  let mut results = Vec::new();
-  
-  for i in 5..=9 {
-    for j in 5..=9 {
-      if i == j { continue; }
 
-      for k in 5..=9 {
-        if i == k || j == k { continue; }
+  pub fn run(self) -> std::thread::JoinHandle<(Intcode8086, RunOutcome)> {
+    self.processor.process()
+  }
+}
 
-        for l in 5..=9 {
-          if i == l || j == l || k == l { continue; }
+// Every ordering of the given phase settings, each used once.
+pub fn phase_settings(phases: RangeInclusive<u8>) -> Vec<Vec<u8>> {
+  let count = phases.clone().count();
+  phases.permutations(count).collect()
+}
 
-          for m in 5..=9 {
-            if i == m || j == m || k == m || l == m { continue; }
+// Chains one amplifier per phase setting, with the last one feeding back into the first, and
+// returns the last signal the final amplifier sends. Without a feedback loop in the program the
+// chain simply runs once through.
+pub fn amplify(program: &[i64], phases: &[u8]) -> Result<i64, Box<dyn Error>> {
+  let mut cpus : Vec<Intcode8086> = phases.iter().map(|_| Intcode8086::initialize(program.to_vec())).collect();
+  let mut ports : Vec<BusReader<i64>> = cpus.iter_mut().map(|cpu| cpu.get_output_port()).collect();
+  let mut output = cpus.last_mut().ok_or("No phase settings to amplify with")?.get_output_port();
+
+  ports.rotate_right(1);
+
+  let handles : Vec<_> = cpus.into_iter().zip(phases).zip(ports).enumerate().map(|(index, ((cpu, phase), port))| {
+    if index == 0 {
+      Amplifier::create(*phase, 0, cpu, port).run()
+    } else {
+      Amplifier::create_no_value(*phase, cpu, port).run()
+    }
+  }).collect();
 
-            results.push(vec![i, j, k, l, m]);
-          }
-        }
-      }
+  for (index, handle) in handles.into_iter().enumerate() {
+    match handle.join().map_err(|_| format!("Amplifier {} panicked", index))? {
+      (_, RunOutcome::Halted) => (),
+      (_, outcome) => return Err(format!("Amplifier {} stopped with {:?}", index, outcome).into())
     }
   }
 
-  results
+  output.iter().last().ok_or_else(|| "The last amplifier sent no signal".into())
 }
 
-impl Amplifier {
-  pub fn run(self) -> std::thread::JoinHandle<Intcode8086> {
-    self.processor.process()
+pub fn max_signal(program: &[i64], phases: RangeInclusive<u8>) -> Result<i64, Box<dyn Error>> {
+  let mut max = None;
+
+  for setting in phase_settings(phases) {
+    let signal = amplify(program, &setting)?;
+    max = max.max(Some(signal));
   }
+
+  max.ok_or_else(|| "There are no phase settings to try".into())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-
-  fn parse_csv(input: &str) -> Vec<i32> {
-    input
-        .split(",")
-        .map(|s| s.trim())
-        .map(|s| s.parse::<i32>().unwrap())
-        .collect()
+  use super::*;
+
+  fn example(name: &str) -> Vec<i64> {
+    Input::example(7, name).unwrap().csv().unwrap()
   }
 
   #[test]
-  fn given_input_part1_1() { 
-    let instructions = "3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0";
-    let mut cpu0 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu1 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu2 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu3 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu4 = Intcode8086::initialize(parse_csv(instructions));
-
-    let io0 = cpu0.get_output_port();
-    let io1 = cpu1.get_output_port();
-    let io2 = cpu2.get_output_port();
-    let io3 = cpu3.get_output_port();
-    let io4 = cpu4.get_output_port();
-
-    let mut output : BusReader<i32> = cpu4.get_output_port();
-
-    let amp0 = Amplifier::create(4, 0, cpu0, io4);
-    let amp1 = Amplifier::create_no_value(3, cpu1, io0);
-    let amp2 = Amplifier::create_no_value(2, cpu2, io1);
-    let amp3 = Amplifier::create_no_value(1, cpu3, io2);
-    let amp4 = Amplifier::create_no_value(0, cpu4, io3);
-
-    amp0.run();
-    amp1.run();
-    amp2.run();
-    amp3.run();
-    amp4.run().join();
-
-    let mut max = 0;
-
-    loop {
-      match output.recv() {
-        Ok(v) => max = v,
-        Err(e) => break
-      };
-    }
-    
-    assert_eq!(max, 43210);
+  fn test_phase_settings() {
+    let settings = phase_settings(5..=9);
+
+    assert_eq!(settings.len(), 120);
+    assert_eq!(settings[0], vec![5, 6, 7, 8, 9]);
+    assert!(settings.iter().all(|s| s.iter().unique().count() == 5));
   }
 
   #[test]
-  fn given_input_part1_2() { 
-    let instructions = "3,23,3,24,1002,24,10,24,1002,23,-1,23,101,5,23,23,1,24,23,23,4,23,99,0,0";
-    let mut cpu0 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu1 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu2 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu3 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu4 = Intcode8086::initialize(parse_csv(instructions));
-
-    let io0 = cpu0.get_output_port();
-    let io1 = cpu1.get_output_port();
-    let io2 = cpu2.get_output_port();
-    let io3 = cpu3.get_output_port();
-    let io4 = cpu4.get_output_port();
-
-    let mut output : BusReader<i32> = cpu4.get_output_port();
-
-    let amp0 = Amplifier::create(0, 0, cpu0, io4);
-    let amp1 = Amplifier::create_no_value(1, cpu1, io0);
-    let amp2 = Amplifier::create_no_value(2, cpu2, io1);
-    let amp3 = Amplifier::create_no_value(3, cpu3, io2);
-    let amp4 = Amplifier::create_no_value(4, cpu4, io3);
-
-    amp0.run();
-    amp1.run();
-    amp2.run();
-    amp3.run();
-    amp4.run().join();
-
-    let mut max = 0;
-
-    loop {
-      match output.recv() {
-        Ok(v) => max = v,
-        Err(e) => break
-      };
-    }
-    
-    assert_eq!(max, 54321);
+  fn given_input_part1_1() {
+    assert_eq!(amplify(&example("example1"), &[4, 3, 2, 1, 0]).unwrap(), 43210);
   }
 
   #[test]
-  fn given_input_part1_3() { 
-    let instructions = "3,31,3,32,1002,32,10,32,1001,31,-2,31,1007,31,0,33,1002,33,7,33,1,33,31,31,1,32,31,31,4,31,99,0,0,0";
-    let mut cpu0 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu1 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu2 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu3 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu4 = Intcode8086::initialize(parse_csv(instructions));
-
-    let io0 = cpu0.get_output_port();
-    let io1 = cpu1.get_output_port();
-    let io2 = cpu2.get_output_port();
-    let io3 = cpu3.get_output_port();
-    let io4 = cpu4.get_output_port();
-
-    let mut output : BusReader<i32> = cpu4.get_output_port();
-
-    let amp0 = Amplifier::create(1, 0, cpu0, io4);
-    let amp1 = Amplifier::create_no_value(0, cpu1, io0);
-    let amp2 = Amplifier::create_no_value(4, cpu2, io1);
-    let amp3 = Amplifier::create_no_value(3, cpu3, io2);
-    let amp4 = Amplifier::create_no_value(2, cpu4, io3);
-
-    amp0.run();
-    amp1.run();
-    amp2.run();
-    amp3.run();
-    amp4.run().join();
-
-    let mut max = 0;
-
-    loop {
-      match output.recv() {
-        Ok(v) => max = v,
-        Err(e) => break
-      };
-    }
-    
-    assert_eq!(max, 65210);
+  fn given_input_part1_2() {
+    assert_eq!(amplify(&example("example2"), &[0, 1, 2, 3, 4]).unwrap(), 54321);
   }
 
   #[test]
-  fn given_input_part2_1() { 
-    let instructions = "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
-    let mut cpu0 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu1 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu2 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu3 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu4 = Intcode8086::initialize(parse_csv(instructions));
-
-    let io0 = cpu0.get_output_port();
-    let io1 = cpu1.get_output_port();
-    let io2 = cpu2.get_output_port();
-    let io3 = cpu3.get_output_port();
-    let io4 = cpu4.get_output_port();
-
-    let mut output : BusReader<i32> = cpu4.get_output_port();
-
-    let amp0 = Amplifier::create(9, 0, cpu0, io4);
-    let amp1 = Amplifier::create_no_value(8, cpu1, io0);
-    let amp2 = Amplifier::create_no_value(7, cpu2, io1);
-    let amp3 = Amplifier::create_no_value(6, cpu3, io2);
-    let amp4 = Amplifier::create_no_value(5, cpu4, io3);
-
-    amp0.run();
-    amp1.run();
-    amp2.run();
-    amp3.run();
-    amp4.run().join();
-
-    let mut max = 0;
-
-    loop {
-      match output.recv() {
-        Ok(v) => max = v,
-        Err(e) => break
-      };
-    }
-    
-    assert_eq!(max, 139629729);
+  fn given_input_part1_3() {
+    assert_eq!(amplify(&example("example3"), &[1, 0, 4, 3, 2]).unwrap(), 65210);
   }
 
-  
   #[test]
-  fn given_input_part2_2() { 
-    let instructions = "3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,-5,54,1105,1,12,1,53,54,53,1008,54,0,55,1001,55,1,55,2,53,55,53,4,53,1001,56,-1,56,1005,56,6,99,0,0,0,0,10";
-    let mut cpu0 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu1 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu2 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu3 = Intcode8086::initialize(parse_csv(instructions));
-    let mut cpu4 = Intcode8086::initialize(parse_csv(instructions));
-
-    let io0 = cpu0.get_output_port();
-    let io1 = cpu1.get_output_port();
-    let io2 = cpu2.get_output_port();
-    let io3 = cpu3.get_output_port();
-    let io4 = cpu4.get_output_port();
-
-    let mut output : BusReader<i32> = cpu4.get_output_port();
-
-    let amp0 = Amplifier::create(9, 0, cpu0, io4);
-    let amp1 = Amplifier::create_no_value(7, cpu1, io0);
-    let amp2 = Amplifier::create_no_value(8, cpu2, io1);
-    let amp3 = Amplifier::create_no_value(5, cpu3, io2);
-    let amp4 = Amplifier::create_no_value(6, cpu4, io3);
-
-    amp0.run();
-    amp1.run();
-    amp2.run();
-    amp3.run();
-    amp4.run().join();
-
-    let mut max = 0;
-
-    loop {
-      match output.recv() {
-        Ok(v) => max = v,
-        Err(e) => break
-      };
-    }
-    
-    assert_eq!(max, 18216);
+  fn given_input_part2_1() {
+    assert_eq!(amplify(&example("example4"), &[9, 8, 7, 6, 5]).unwrap(), 139629729);
+  }
+
+  #[test]
+  fn given_input_part2_2() {
+    assert_eq!(amplify(&example("example5"), &[9, 7, 8, 5, 6]).unwrap(), 18216);
+  }
+
+  #[test]
+  fn test_max_signal() {
+    assert_eq!(max_signal(&example("example1"), 0..=4).unwrap(), 43210);
+    assert_eq!(max_signal(&example("example4"), 5..=9).unwrap(), 139629729);
   }
-}
\ No newline at end of file
+}
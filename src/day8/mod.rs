@@ -0,0 +1,82 @@
+use std::error::Error;
+use super::grid::Grid;
+use super::inputhandling::Input;
+use super::solution::{ Answer, Solution };
+
+pub const WIDTH: usize = 25;
+pub const HEIGHT: usize = 6;
+
+pub struct SpaceImageFormat;
+
+impl Solution for SpaceImageFormat {
+  const DAY: u8 = 8;
+  const TITLE: &'static str = "Space Image Format";
+
+  type Parsed = Vec<Grid<u32>>;
+
+  fn parse(input: &Input) -> Result<Vec<Grid<u32>>, Box<dyn Error>> {
+    let digits = input.chars().iter().map(|c| c.to_digit(10).ok_or_else(|| format!("'{}' isn't a pixel", c))).collect::<Result<Vec<u32>, String>>()?;
+    layers(&digits, WIDTH, HEIGHT)
+  }
+
+  fn part1(layers: &Vec<Grid<u32>>) -> Result<Answer, Box<dyn Error>> {
+    Ok(checksum(layers).ok_or("The image has no layers")?.into())
+  }
+
+  fn part2(layers: &Vec<Grid<u32>>) -> Result<Answer, Box<dyn Error>> {
+    Ok(render(layers).ok_or("The image has no layers")?.into())
+  }
+}
+
+pub fn layers(digits: &[u32], width: usize, height: usize) -> Result<Vec<Grid<u32>>, Box<dyn Error>> {
+  let size = width * height;
+
+  if size == 0 || !digits.len().is_multiple_of(size) {
+    return Err(format!("{} pixels don't split into {}x{} layers", digits.len(), width, height).into());
+  }
+
+  digits.chunks(size).map(|layer| Grid::from_cells(width, height, layer.to_vec())).collect()
+}
+
+// The number of 1 digits times the number of 2 digits, on the layer with the fewest 0 digits.
+pub fn checksum(layers: &[Grid<u32>]) -> Option<usize> {
+  let layer = layers.iter().min_by_key(|l| l.find_all(&0).count())?;
+
+  Some(layer.find_all(&1).count() * layer.find_all(&2).count())
+}
+
+// Stacks the layers front to back: 0 is black, 1 is white and 2 is transparent.
+pub fn render(layers: &[Grid<u32>]) -> Option<Grid<char>> {
+  let front = layers.first()?;
+
+  let cells = front.points().map(|point| {
+    match layers.iter().map(|l| l[point]).find(|pixel| *pixel != 2) {
+      Some(1) => '#',
+      _ => '.'
+    }
+  }).collect();
+
+  Grid::from_cells(front.width(), front.height(), cells).ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_layers_and_checksum() {
+    let layers = layers(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2], 3, 2).unwrap();
+
+    assert_eq!(layers.len(), 2);
+    assert_eq!(layers[1].row(0), &[7, 8, 9]);
+    assert_eq!(checksum(&layers), Some(1));
+    assert!(super::layers(&[1, 2, 3], 2, 2).is_err());
+  }
+
+  #[test]
+  fn test_render() {
+    let layers = layers(&[0, 2, 2, 2, 1, 1, 2, 2, 2, 2, 1, 2, 0, 0, 0, 0], 2, 2).unwrap();
+
+    assert_eq!(render(&layers).unwrap().to_string(), ".#\n#.\n");
+  }
+}
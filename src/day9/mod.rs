@@ -0,0 +1,163 @@
+use std::error::Error;
+use super::inputhandling::Input;
+use super::intcode_8086::RunOutcome;
+use super::intcode_transcript;
+use super::solution::{ Answer, Solution };
+
+pub const TEST_MODE: i64 = 1;
+pub const SENSOR_MODE: i64 = 2;
+
+pub struct SensorBoost;
+
+impl Solution for SensorBoost {
+  const DAY: u8 = 9;
+  const TITLE: &'static str = "Sensor Boost";
+
+  type Parsed = Vec<i64>;
+
+  fn parse(input: &Input) -> Result<Vec<i64>, Box<dyn Error>> {
+    Ok(input.csv()?)
+  }
+
+  fn part1(program: &Vec<i64>) -> Result<Answer, Box<dyn Error>> {
+    Ok(self_test(program)?.into())
+  }
+
+  fn part2(program: &Vec<i64>) -> Result<Answer, Box<dyn Error>> {
+    Ok(coordinates(program)?.into())
+  }
+}
+
+// An instruction as BOOST reports it: the opcode with its parameter modes in front.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Malfunction {
+  pub instruction: i64
+}
+
+impl Malfunction {
+  pub fn opcode(&self) -> i64 {
+    self.instruction % 100
+  }
+
+  // The mode of each parameter, first parameter first, as far as the instruction spells them out.
+  pub fn modes(&self) -> Vec<i64> {
+    let mut modes = Vec::new();
+    let mut rest = self.instruction / 100;
+
+    while rest > 0 {
+      modes.push(rest % 10);
+      rest /= 10;
+    }
+
+    modes
+  }
+}
+
+impl std::fmt::Display for Malfunction {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let modes = self.modes();
+
+    if modes.is_empty() {
+      write!(f, "opcode {}", self.opcode())
+    } else {
+      let modes = modes.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(", ");
+      write!(f, "opcode {} (modes {})", self.opcode(), modes)
+    }
+  }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum BoostError {
+  Stopped { outcome: RunOutcome, outputs: Vec<i64> },
+  NoOutput,
+  // In test mode every output before the keycode names an instruction that misbehaved.
+  Malfunctioning { malfunctions: Vec<Malfunction>, keycode: i64 },
+  // Sensor mode should produce the coordinates and nothing else.
+  UnexpectedOutputs(Vec<i64>)
+}
+
+impl std::fmt::Display for BoostError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      BoostError::Stopped { outcome, outputs } => write!(f, "BOOST stopped with {:?} after {} outputs", outcome, outputs.len()),
+      BoostError::NoOutput => write!(f, "BOOST produced no output"),
+      BoostError::Malfunctioning { malfunctions, .. } => {
+        let list = malfunctions.iter().map(|m| m.to_string()).collect::<Vec<_>>().join("; ");
+        write!(f, "BOOST reports {} malfunctioning instructions: {}", malfunctions.len(), list)
+      },
+      BoostError::UnexpectedOutputs(outputs) => write!(f, "BOOST produced {} outputs in sensor mode, expected 1", outputs.len())
+    }
+  }
+}
+
+impl Error for BoostError {}
+
+pub fn run_boost(program: &[i64], mode: i64) -> Result<Vec<i64>, BoostError> {
+  intcode_transcript::run_to_outputs(program, &[mode]).map_err(|(outcome, outputs)| BoostError::Stopped { outcome, outputs })
+}
+
+// The last output is the keycode; anything before it is a malfunctioning instruction.
+pub fn check_outputs(outputs: Vec<i64>) -> Result<i64, BoostError> {
+  match outputs.split_last() {
+    None => Err(BoostError::NoOutput),
+    Some((keycode, [])) => Ok(*keycode),
+    Some((keycode, malfunctions)) => Err(BoostError::Malfunctioning {
+      malfunctions: malfunctions.iter().map(|i| Malfunction { instruction: *i }).collect(),
+      keycode: *keycode
+    })
+  }
+}
+
+pub fn self_test(program: &[i64]) -> Result<i64, BoostError> {
+  check_outputs(run_boost(program, TEST_MODE)?)
+}
+
+pub fn coordinates(program: &[i64]) -> Result<i64, BoostError> {
+  match run_boost(program, SENSOR_MODE)?.as_slice() {
+    [] => Err(BoostError::NoOutput),
+    [coordinates] => Ok(*coordinates),
+    outputs => Err(BoostError::UnexpectedOutputs(outputs.to_vec()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_quine() {
+    let program = Input::inline("109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99").csv().unwrap();
+
+    assert_eq!(run_boost(&program, TEST_MODE), Ok(program.clone()));
+  }
+
+  #[test]
+  fn test_large_numbers() {
+    assert_eq!(self_test(&Input::inline("1102,34915192,34915192,7,4,7,99,0").csv().unwrap()), Ok(1219070632396864));
+    assert_eq!(coordinates(&Input::inline("104,1125899906842624,99").csv().unwrap()), Ok(1125899906842624));
+  }
+
+  #[test]
+  fn test_malfunctions_are_reported() {
+    let error = self_test(&Input::inline("104,203,104,1,104,42,99").csv().unwrap()).unwrap_err();
+
+    assert_eq!(error, BoostError::Malfunctioning { malfunctions: vec![Malfunction { instruction: 203 }, Malfunction { instruction: 1 }], keycode: 42 });
+    assert_eq!(error.to_string(), "BOOST reports 2 malfunctioning instructions: opcode 3 (modes 2); opcode 1");
+  }
+
+  #[test]
+  fn test_malfunction_modes() {
+    let malfunction = Malfunction { instruction: 21101 };
+
+    assert_eq!(malfunction.opcode(), 1);
+    assert_eq!(malfunction.modes(), vec![1, 1, 2]);
+    assert_eq!(malfunction.to_string(), "opcode 1 (modes 1, 1, 2)");
+  }
+
+  #[test]
+  fn test_bad_runs() {
+    assert_eq!(self_test(&Input::inline("99").csv().unwrap()), Err(BoostError::NoOutput));
+    assert_eq!(coordinates(&Input::inline("104,1,104,2,99").csv().unwrap()), Err(BoostError::UnexpectedOutputs(vec![1, 2])));
+    assert!(matches!(self_test(&Input::inline("3,0,3,0,99").csv().unwrap()), Err(BoostError::Stopped { outcome: RunOutcome::InputExhausted, .. })));
+  }
+}
@@ -36,21 +36,17 @@ where
                 Some(peek) => {
                     if x == peek {
                         match self.iter.peek().cloned() {
-                            Some(peek3) => {
-                                if x == peek3 {
-                                    self.iter.next();
-                                    self.iter.next();
-
-                                    while self.iter.peek().cloned() == Some(peek3.clone()) {
-                                        self.iter.next();
-                                    }
+                            Some(peek3) if x == peek3 => {
+                                self.iter.next();
+                                self.iter.next();
 
-                                    return self.iter.next();
-                                } else {
-                                    return Some(x);
+                                while self.iter.peek().cloned() == Some(peek3.clone()) {
+                                    self.iter.next();
                                 }
+
+                                return self.iter.next();
                             }
-                            None => return Some(x),
+                            _ => return Some(x),
                         };
                     }
 
@@ -0,0 +1,238 @@
+use std::error::Error;
+use std::ops::{ Index, IndexMut };
+use super::day3::Point;
+
+// A dense, row-major grid. Points are (column, row): x grows to the right and y grows down.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Grid<T> {
+  width: usize,
+  height: usize,
+  cells: Vec<T>
+}
+
+const FOUR_NEIGHBOURS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const EIGHT_NEIGHBOURS: [(i32, i32); 8] = [(-1, -1), (0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0)];
+
+impl<T> Grid<T> {
+  pub fn new(width: usize, height: usize, fill: T) -> Grid<T> where T: Clone {
+    Grid { width, height, cells: vec![fill; width * height] }
+  }
+
+  pub fn from_cells(width: usize, height: usize, cells: Vec<T>) -> Result<Grid<T>, Box<dyn Error>> {
+    if cells.len() != width * height {
+      return Err(format!("A {}x{} grid needs {} cells but was given {}", width, height, width * height, cells.len()).into());
+    }
+
+    Ok(Grid { width, height, cells })
+  }
+
+  pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>, Box<dyn Error>> {
+    let width = rows.first().map(|r| r.len()).unwrap_or(0);
+    let height = rows.len();
+
+    if let Some(y) = rows.iter().position(|r| r.len() != width) {
+      return Err(format!("Row {} has {} cells but row 0 has {}", y, rows[y].len(), width).into());
+    }
+
+    Ok(Grid { width, height, cells: rows.into_iter().flatten().collect() })
+  }
+
+  pub fn width(&self) -> usize {
+    self.width
+  }
+
+  pub fn height(&self) -> usize {
+    self.height
+  }
+
+  pub fn contains(&self, point: Point) -> bool {
+    point.x >= 0 && point.y >= 0 && (point.x as usize) < self.width && (point.y as usize) < self.height
+  }
+
+  fn offset(&self, point: Point) -> Option<usize> {
+    if self.contains(point) {
+      Some(point.y as usize * self.width + point.x as usize)
+    } else {
+      None
+    }
+  }
+
+  pub fn get(&self, point: Point) -> Option<&T> {
+    self.offset(point).map(|o| &self.cells[o])
+  }
+
+  pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+    self.offset(point).map(move |o| &mut self.cells[o])
+  }
+
+  pub fn points(&self) -> impl Iterator<Item = Point> {
+    let width = self.width;
+    (0..self.width * self.height).map(move |o| Point::new((o % width) as i32, (o / width) as i32))
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+    self.points().zip(self.cells.iter())
+  }
+
+  fn around<'a>(&'a self, point: Point, offsets: &'a [(i32, i32)]) -> impl Iterator<Item = (Point, &'a T)> {
+    offsets
+      .iter()
+      .map(move |(dx, dy)| Point::new(point.x + dx, point.y + dy))
+      .filter_map(move |p| self.get(p).map(|v| (p, v)))
+  }
+
+  // Up, right, down, left; neighbours outside the grid are skipped.
+  pub fn neighbours4(&self, point: Point) -> impl Iterator<Item = (Point, &T)> {
+    self.around(point, &FOUR_NEIGHBOURS)
+  }
+
+  // Clockwise from the top-left; neighbours outside the grid are skipped.
+  pub fn neighbours8(&self, point: Point) -> impl Iterator<Item = (Point, &T)> {
+    self.around(point, &EIGHT_NEIGHBOURS)
+  }
+
+  pub fn row(&self, y: usize) -> &[T] {
+    &self.cells[y * self.width..(y + 1) * self.width]
+  }
+
+  pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+    // chunks panics on zero, and a zero-width grid has no cells to chunk anyway.
+    self.cells.chunks(self.width.max(1))
+  }
+
+  pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+    assert!(x < self.width, "Column {} is outside a grid {} wide", x, self.width);
+    self.cells.iter().skip(x).step_by(self.width)
+  }
+
+  pub fn transpose(&self) -> Grid<T> where T: Clone {
+    let cells = (0..self.width).flat_map(|x| self.column(x).cloned()).collect();
+    Grid { width: self.height, height: self.width, cells }
+  }
+
+  pub fn find(&self, value: &T) -> Option<Point> where T: PartialEq {
+    self.iter().find(|(_, v)| *v == value).map(|(p, _)| p)
+  }
+
+  pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Point> + 'a where T: PartialEq {
+    self.iter().filter(move |(_, v)| *v == value).map(|(p, _)| p)
+  }
+
+  pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+    Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+  }
+}
+
+impl<T> Index<Point> for Grid<T> {
+  type Output = T;
+
+  fn index(&self, point: Point) -> &T {
+    match self.get(point) {
+      Some(v) => v,
+      None => panic!("{:?} is outside a {}x{} grid", point, self.width, self.height)
+    }
+  }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+  fn index_mut(&mut self, point: Point) -> &mut T {
+    let (width, height) = (self.width, self.height);
+
+    match self.get_mut(point) {
+      Some(v) => v,
+      None => panic!("{:?} is outside a {}x{} grid", point, width, height)
+    }
+  }
+}
+
+impl<T: std::fmt::Display> std::fmt::Display for Grid<T> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    for row in self.rows().take(self.height) {
+      for cell in row {
+        write!(f, "{}", cell)?;
+      }
+
+      writeln!(f)?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::inputhandling::Input;
+
+  fn sample() -> Grid<char> {
+    Input::inline("abc\ndef\n").grid().unwrap()
+  }
+
+  #[test]
+  fn test_indexing() {
+    let mut grid = sample();
+
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert_eq!(grid[Point::new(2, 1)], 'f');
+    assert_eq!(grid.get(Point::new(3, 0)), None);
+    assert_eq!(grid.get(Point::new(0, -1)), None);
+
+    grid[Point::new(0, 0)] = 'z';
+    assert_eq!(grid.row(0), &['z', 'b', 'c']);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_index_out_of_bounds() {
+    let _ = sample()[Point::new(-1, 0)];
+  }
+
+  #[test]
+  fn test_neighbours() {
+    let grid = sample();
+
+    let four = grid.neighbours4(Point::new(0, 0)).map(|(_, v)| *v).collect::<String>();
+    assert_eq!(four, "bd");
+
+    let eight = grid.neighbours8(Point::new(1, 0)).map(|(_, v)| *v).collect::<String>();
+    assert_eq!(eight, "cfeda");
+  }
+
+  #[test]
+  fn test_rows_columns_transpose() {
+    let grid = sample();
+
+    assert_eq!(grid.column(1).collect::<String>(), "be");
+    assert_eq!(grid.rows().count(), 2);
+
+    let transposed = grid.transpose();
+    assert_eq!((transposed.width(), transposed.height()), (2, 3));
+    assert_eq!(transposed.to_string(), "ad\nbe\ncf\n");
+    assert_eq!(transposed.transpose(), grid);
+  }
+
+  #[test]
+  fn test_find() {
+    let grid = Input::inline("#.#\n..#").grid().unwrap();
+
+    assert_eq!(grid.find(&'#'), Some(Point::new(0, 0)));
+    assert_eq!(grid.find_all(&'#').count(), 3);
+    assert_eq!(grid.find(&'x'), None);
+  }
+
+  #[test]
+  fn test_construction_errors() {
+    assert!(Grid::from_rows(vec![vec![1, 2], vec![3]]).is_err());
+    assert!(Grid::from_cells(2, 2, vec![1, 2, 3]).is_err());
+    assert!(Input::inline("ab\nc\n").grid().is_err());
+  }
+
+  #[test]
+  fn test_from_cells_and_map() {
+    let grid = Grid::from_cells(3, 2, vec![0, 1, 2, 2, 1, 0]).unwrap();
+    let pretty = grid.map(|v| if *v == 1 { '#' } else { ' ' });
+
+    assert_eq!(pretty.to_string(), " # \n # \n");
+    assert_eq!(Input::inline("012\n210").grid_with(|c| c.to_digit(10).ok_or("not a digit")).unwrap(), grid.map(|v| *v as u32));
+  }
+}
@@ -1,77 +1,835 @@
 use std::error::Error;
 use std::fs::File;
 use std::io::prelude::*;
-use std::path::Path;
-
-pub fn parse_input<T>(
-    day: u8,
-    parser: fn(&str) -> Result<T, Box<dyn Error>>,
-) -> Result<Vec<T>, Box<dyn Error>> {
-    let pathstr = format!(".\\src\\day{}\\input.txt", day);
-    let path = Path::new(&pathstr);
-    let mut file = File::open(path)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use super::grid::Grid;
+
+pub const INPUT_ROOT_VAR: &str = "AOC_INPUT_ROOT";
+pub const CONFIG_FILE: &str = "aoc.config";
+
+static INPUT_ROOT_OVERRIDE: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+#[derive(Debug)]
+pub struct InputPathError {
+    pub path: PathBuf,
+    pub source: std::io::Error,
+}
+
+impl std::fmt::Display for InputPathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Couldn't read {}: {}", self.path.display(), self.source)
+    }
+}
+
+impl Error for InputPathError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+// A token that failed to parse, with enough context to point at it in the file. The
+// details are boxed to keep parse results small; they're reachable as fields through Deref.
+#[derive(Debug)]
+pub struct InputError {
+    context: Box<InputErrorContext>,
+}
+
+#[derive(Debug)]
+pub struct InputErrorContext {
+    pub day: Option<u8>,
+    pub section: Option<usize>,
+    pub path: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+    pub field: Option<usize>,
+    pub token: String,
+    pub line_text: String,
+    pub cause: Box<dyn Error>,
+}
+
+impl std::ops::Deref for InputError {
+    type Target = InputErrorContext;
+
+    fn deref(&self) -> &InputErrorContext {
+        &self.context
+    }
+}
+
+impl std::fmt::Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day {}, ", day)?;
+        }
+
+        if let Some(section) = self.section {
+            write!(f, "section {}, ", section)?;
+        }
+
+        match &self.path {
+            Some(path) => write!(f, "{}:{}:{}", path.display(), self.line, self.column)?,
+            None => write!(f, "line {}, column {}", self.line, self.column)?,
+        };
+
+        if let Some(field) = self.field {
+            write!(f, " (field {})", field)?;
+        }
+
+        writeln!(f, ": couldn't parse '{}': {}", self.token, self.cause)?;
+
+        let gutter = " ".repeat(self.line.to_string().len());
+        let indent = " ".repeat(self.column - 1);
+        let carets = "^".repeat(self.token.chars().count().max(1));
+
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", self.line, self.line_text)?;
+        write!(f, "{} | {}{}", gutter, indent, carets)
+    }
+}
+
+impl Error for InputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.cause.as_ref())
+    }
+}
+
+// Takes precedence over the environment and the config file; the runner sets this from
+// its --input-root flag.
+pub fn set_input_root<P: Into<PathBuf>>(path: P) {
+    *INPUT_ROOT_OVERRIDE.write().unwrap() = Some(path.into());
+}
+
+// Resolved in order: set_input_root, the AOC_INPUT_ROOT variable, an `input_root` entry in
+// aoc.config (in the working directory, then the crate root), and finally the crate's src.
+pub fn input_root() -> PathBuf {
+    if let Some(path) = INPUT_ROOT_OVERRIDE.read().unwrap().as_ref() {
+        return path.clone();
+    }
+
+    if let Some(path) = std::env::var_os(INPUT_ROOT_VAR) {
+        return PathBuf::from(path);
+    }
+
+    let crate_root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    for dir in [PathBuf::from("."), crate_root.to_path_buf()].iter() {
+        if let Ok(contents) = std::fs::read_to_string(dir.join(CONFIG_FILE)) {
+            if let Some(path) = parse_config(&contents) {
+                return dir.join(path);
+            }
+        }
+    }
+
+    crate_root.join("src")
+}
+
+pub fn input_path(day: u8) -> PathBuf {
+    day_dir(day).join("input.txt")
+}
+
+pub fn day_dir(day: u8) -> PathBuf {
+    input_root().join(format!("day{}", day))
+}
+
+// Expected answers kept next to an example as `<name>.answer`, one `partN: answer` per line.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExpectedAnswers {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+impl ExpectedAnswers {
+    pub fn parse(contents: &str) -> Result<ExpectedAnswers, Box<dyn Error>> {
+        let mut answers = ExpectedAnswers::default();
+
+        for line in contents.lines().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            let (part, answer) = match line.find(':') {
+                Some(index) => (line[..index].trim(), line[index + 1..].trim().to_string()),
+                None => return Err(format!("Expected 'partN: answer' but found '{}'", line).into()),
+            };
+
+            match part {
+                "part1" => answers.part1 = Some(answer),
+                "part2" => answers.part2 = Some(answer),
+                other => return Err(format!("Unknown part '{}'", other).into()),
+            };
+        }
+
+        Ok(answers)
+    }
+
+    pub fn part(&self, part: u8) -> Option<&str> {
+        match part {
+            1 => self.part1.as_deref(),
+            2 => self.part2.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Example {
+    pub day: u8,
+    pub name: String,
+    pub path: PathBuf,
+    pub expected: ExpectedAnswers,
+}
+
+impl Example {
+    pub fn input(&self) -> Result<Input, Box<dyn Error>> {
+        Ok(Input::from_file(&self.path)?.with_day(self.day))
+    }
+}
+
+// Every `example*.txt` in the day's folder, in name order.
+pub fn examples(day: u8) -> Result<Vec<Example>, Box<dyn Error>> {
+    let dir = day_dir(day);
+    let entries = std::fs::read_dir(&dir).map_err(|source| InputPathError { path: dir.clone(), source })?;
+
+    let mut examples = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let name = match (path.file_stem().and_then(|s| s.to_str()), path.extension()) {
+            (Some(name), Some(ext)) if ext == "txt" && name.starts_with("example") => name.to_string(),
+            _ => continue,
+        };
+
+        examples.push(example_at(day, name, path)?);
+    }
+
+    examples.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(examples)
+}
+
+pub fn example(day: u8, name: &str) -> Result<Example, Box<dyn Error>> {
+    example_at(day, name.to_string(), day_dir(day).join(format!("{}.txt", name)))
+}
+
+fn example_at(day: u8, name: String, path: PathBuf) -> Result<Example, Box<dyn Error>> {
+    let answer_path = path.with_extension("answer");
+    let expected = if answer_path.is_file() {
+        ExpectedAnswers::parse(&read_input(&answer_path)?)?
+    } else {
+        ExpectedAnswers::default()
+    };
+
+    Ok(Example { day, name, path, expected })
+}
+
+fn parse_config(contents: &str) -> Option<PathBuf> {
     contents
         .lines()
         .map(|s| s.trim())
-        .map(|s| parser(s))
-        .collect()
+        .filter(|s| !s.starts_with('#'))
+        .filter_map(|s| {
+            let mut parts = s.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) if key.trim() == "input_root" => {
+                    Some(PathBuf::from(value.trim().trim_matches('"')))
+                }
+                _ => None,
+            }
+        })
+        .next_back()
 }
 
-pub fn get_input_chars(
-    day: u8,
-) -> Result<Vec<char>, Box<dyn Error>> {
-    let pathstr = format!(".\\src\\day{}\\input.txt", day);
-    let path = Path::new(&pathstr);
-    let mut file = File::open(path)?;
+fn read_input(path: &Path) -> Result<String, Box<dyn Error>> {
     let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    Result::Ok(contents.trim().chars().collect::<Vec<char>>())
+
+    File::open(path)
+        .and_then(|mut file| file.read_to_string(&mut contents))
+        .map_err(|source| InputPathError { path: path.to_path_buf(), source })?;
+
+    Ok(contents)
 }
 
-pub fn parse_input_per_line<T>(
-    day: u8,
-    parser: fn(&str) -> Result<T, Box<dyn Error>>,
-) -> Result<Vec<T>, Box<dyn Error>> {
-    let pathstr = format!(".\\src\\day{}\\input.txt", day);
-    let path = Path::new(&pathstr);
-    let mut file = File::open(path)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    contents
-        .lines()
-        .map(|mut s| parser(s.trim()))
-        .collect()
+// A puzzle input read once; clones share the same contents, so part 1 and part 2 can each
+// parse it their own way without touching the disk again.
+#[derive(Clone, Debug)]
+pub struct Input {
+    day: Option<u8>,
+    path: Option<PathBuf>,
+    contents: Arc<str>,
 }
 
-pub fn parse_input_csv_per_line<T>(
-    day: u8,
-    parser: fn(&str) -> Result<T, Box<dyn Error>>,
-) -> Result<Vec<Vec<T>>, Box<dyn Error>> {
-    let pathstr = format!(".\\src\\day{}\\input.txt", day);
-    let path = Path::new(&pathstr);
-    let mut file = File::open(path)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    contents
-        .lines()
-        .map(|s| s.trim().split(",").map(|x| parser(x)).collect())
-        .collect()
+impl Input {
+    pub fn for_day(day: u8) -> Result<Input, Box<dyn Error>> {
+        let path = input_path(day);
+        let contents = read_input(&path)?;
+
+        Ok(Input { day: Some(day), path: Some(path), contents: contents.into() })
+    }
+
+    pub fn example(day: u8, name: &str) -> Result<Input, Box<dyn Error>> {
+        example(day, name)?.input()
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Input, Box<dyn Error>> {
+        let path = path.as_ref().to_path_buf();
+        let contents = read_input(&path)?;
+
+        Ok(Input { day: None, path: Some(path), contents: contents.into() })
+    }
+
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Input, Box<dyn Error>> {
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents)?;
+
+        Ok(Input::inline(&contents))
+    }
+
+    pub fn from_stdin() -> Result<Input, Box<dyn Error>> {
+        Input::from_reader(std::io::stdin())
+    }
+
+    pub fn inline(contents: &str) -> Input {
+        Input { day: None, path: None, contents: contents.into() }
+    }
+
+    // Tags errors from an input that didn't come from the day's own file with the day anyway.
+    pub fn with_day(mut self, day: u8) -> Input {
+        self.day = Some(day);
+        self
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn contents(&self) -> &str {
+        &self.contents
+    }
+
+    pub fn lines<T>(&self) -> Result<Vec<T>, InputError>
+    where
+        T: FromStr,
+        T::Err: Into<Box<dyn Error>>,
+    {
+        self.whole().lines()
+    }
+
+    pub fn lines_with<T, E, F>(&self, parser: F) -> Result<Vec<T>, InputError>
+    where
+        E: Into<Box<dyn Error>>,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        self.whole().lines_with(parser)
+    }
+
+    pub fn csv<T>(&self) -> Result<Vec<T>, InputError>
+    where
+        T: FromStr,
+        T::Err: Into<Box<dyn Error>>,
+    {
+        self.whole().csv()
+    }
+
+    pub fn csv_with<T, E, F>(&self, parser: F) -> Result<Vec<T>, InputError>
+    where
+        E: Into<Box<dyn Error>>,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        self.whole().csv_with(parser)
+    }
+
+    pub fn csv_per_line<T>(&self) -> Result<Vec<Vec<T>>, InputError>
+    where
+        T: FromStr,
+        T::Err: Into<Box<dyn Error>>,
+    {
+        self.whole().csv_per_line()
+    }
+
+    pub fn csv_per_line_with<T, E, F>(&self, parser: F) -> Result<Vec<Vec<T>>, InputError>
+    where
+        E: Into<Box<dyn Error>>,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        self.whole().csv_per_line_with(parser)
+    }
+
+    pub fn chars(&self) -> Vec<char> {
+        self.whole().chars()
+    }
+
+    pub fn grid(&self) -> Result<Grid<char>, InputError> {
+        self.whole().grid()
+    }
+
+    pub fn grid_with<T, E, F>(&self, parser: F) -> Result<Grid<T>, InputError>
+    where
+        E: Into<Box<dyn Error>>,
+        F: FnMut(char) -> Result<T, E>,
+    {
+        self.whole().grid_with(parser)
+    }
+
+    fn whole(&self) -> Section<'_> {
+        Section { input: self, index: None, text: &self.contents }
+    }
+
+    // Blocks of non-blank lines, read in order; see Sections.
+    pub fn sections(&self) -> Sections<'_> {
+        Sections { input: self, offset: 0, index: 0 }
+    }
+
+    pub fn error_at(&self, token: &str, field: Option<usize>, cause: Box<dyn Error>) -> InputError {
+        self.error_in(None, token, field, cause)
+    }
+
+    fn error_in(&self, section: Option<usize>, token: &str, field: Option<usize>, cause: Box<dyn Error>) -> InputError {
+        let offset = (token.as_ptr() as usize)
+            .checked_sub(self.contents.as_ptr() as usize)
+            .filter(|o| *o <= self.contents.len())
+            .unwrap_or(0);
+
+        let line_start = self.contents[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = self.contents[offset..].find('\n').map(|i| offset + i).unwrap_or_else(|| self.contents.len());
+
+        InputError { context: Box::new(InputErrorContext {
+            day: self.day,
+            section,
+            path: self.path.clone(),
+            line: self.contents[..line_start].matches('\n').count() + 1,
+            column: self.contents[line_start..offset].chars().count() + 1,
+            field,
+            token: token.to_string(),
+            line_text: self.contents[line_start..line_end].trim_end_matches('\r').to_string(),
+            cause,
+        }) }
+    }
 }
 
-pub fn parse_csv_input<T>(
-    day: u8,
-    parser: fn(&str) -> Result<T, Box<dyn Error>>,
-) -> Result<Vec<T>, Box<dyn Error>> {
-    let pathstr = format!(".\\src\\day{}\\input.txt", day);
-    let path = Path::new(&pathstr);
-    let mut file = File::open(path)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    contents
-        .split(",")
-        .map(|s| s.trim())
-        .map(|s| parser(s))
-        .collect()
+// Part of an input, such as one blank-line separated block. Errors still point at the line and
+// column in the whole file and also say which section they came from.
+#[derive(Clone, Copy, Debug)]
+pub struct Section<'a> {
+    input: &'a Input,
+    index: Option<usize>,
+    text: &'a str,
+}
+
+impl<'a> Section<'a> {
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    // Counted from 1; None when the section is the whole input.
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn lines<T>(&self) -> Result<Vec<T>, InputError>
+    where
+        T: FromStr,
+        T::Err: Into<Box<dyn Error>>,
+    {
+        self.lines_with(str::parse::<T>)
+    }
+
+    pub fn lines_with<T, E, F>(&self, mut parser: F) -> Result<Vec<T>, InputError>
+    where
+        E: Into<Box<dyn Error>>,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        self.text
+            .lines()
+            .map(|s| self.parse_token(s.trim(), None, &mut parser))
+            .collect()
+    }
+
+    pub fn csv<T>(&self) -> Result<Vec<T>, InputError>
+    where
+        T: FromStr,
+        T::Err: Into<Box<dyn Error>>,
+    {
+        self.csv_with(str::parse::<T>)
+    }
+
+    pub fn csv_with<T, E, F>(&self, mut parser: F) -> Result<Vec<T>, InputError>
+    where
+        E: Into<Box<dyn Error>>,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        self.text
+            .split(',')
+            .enumerate()
+            .map(|(field, s)| self.parse_token(s.trim(), Some(field), &mut parser))
+            .collect()
+    }
+
+    pub fn csv_per_line<T>(&self) -> Result<Vec<Vec<T>>, InputError>
+    where
+        T: FromStr,
+        T::Err: Into<Box<dyn Error>>,
+    {
+        self.csv_per_line_with(str::parse::<T>)
+    }
+
+    pub fn csv_per_line_with<T, E, F>(&self, mut parser: F) -> Result<Vec<Vec<T>>, InputError>
+    where
+        E: Into<Box<dyn Error>>,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        self.text
+            .lines()
+            .map(|s| {
+                s.trim()
+                    .split(',')
+                    .enumerate()
+                    .map(|(field, x)| self.parse_token(x.trim(), Some(field), &mut parser))
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn chars(&self) -> Vec<char> {
+        self.text.trim().chars().collect()
+    }
+
+    pub fn grid(&self) -> Result<Grid<char>, InputError> {
+        self.grid_with(Ok::<char, Box<dyn Error>>)
+    }
+
+    // Every non-blank line is a row and every character a cell; rows must all be the same width.
+    pub fn grid_with<T, E, F>(&self, mut parser: F) -> Result<Grid<T>, InputError>
+    where
+        E: Into<Box<dyn Error>>,
+        F: FnMut(char) -> Result<T, E>,
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::new();
+
+        for line in self.text.lines().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            let row_width = line.chars().count();
+            let expected = *width.get_or_insert(row_width);
+
+            if row_width != expected {
+                let cause = format!("Row has {} cells but the grid is {} wide", row_width, expected);
+                return Err(self.error_at(line, None, cause.into()));
+            }
+
+            for (index, c) in line.char_indices() {
+                let token = &line[index..index + c.len_utf8()];
+                cells.push(parser(c).map_err(|cause| self.error_at(token, None, cause.into()))?);
+            }
+
+            height += 1;
+        }
+
+        Ok(Grid::from_cells(width.unwrap_or(0), height, cells).expect("Every row was checked against the grid width"))
+    }
+
+    // `token` must be a slice of `self.contents`; its position there gives the line and column.
+    fn parse_token<T, E, F>(&self, token: &str, field: Option<usize>, parser: &mut F) -> Result<T, InputError>
+    where
+        E: Into<Box<dyn Error>>,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        parser(token).map_err(|cause| self.error_at(token, field, cause.into()))
+    }
+
+    pub fn error_at(&self, token: &str, field: Option<usize>, cause: Box<dyn Error>) -> InputError {
+        self.input.error_in(self.index, token, field, cause)
+    }
+}
+
+// A cursor over an input's sections, for inputs made of a header and a body or of several
+// blocks separated by blank lines. Each section can be read with its own parser:
+//
+//     let mut sections = input.sections();
+//     let program = sections.next_as(csv::<i64>)?;
+//     let rules = sections.next_as(lines::<Rule>)?;
+//     sections.finish()?;
+pub struct Sections<'a> {
+    input: &'a Input,
+    offset: usize,
+    index: usize,
+}
+
+impl<'a> Sections<'a> {
+    pub fn next_as<T, F>(&mut self, parser: F) -> Result<T, InputError>
+    where
+        F: FnOnce(&Section<'a>) -> Result<T, InputError>,
+    {
+        match self.next() {
+            Some(section) => parser(&section),
+            None => {
+                let contents = &self.input.contents;
+                let cause = format!("Expected section {} but the input has only {}", self.index + 1, self.index);
+                Err(self.input.error_in(Some(self.index + 1), &contents[contents.len()..], None, cause.into()))
+            }
+        }
+    }
+
+    // Fails if any sections are left unread.
+    pub fn finish(mut self) -> Result<(), InputError> {
+        match self.next() {
+            Some(section) => {
+                let cause = format!("Expected {} sections but found more", self.index - 1);
+                Err(section.error_at(section.text, None, cause.into()))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a> Iterator for Sections<'a> {
+    type Item = Section<'a>;
+
+    fn next(&mut self) -> Option<Section<'a>> {
+        let contents: &'a str = &self.input.contents;
+        let mut start = None;
+        let mut end = self.offset;
+
+        for line in contents[self.offset..].split_inclusive('\n') {
+            let line_start = end;
+            end += line.len();
+
+            if line.trim().is_empty() {
+                if start.is_some() {
+                    break;
+                }
+            } else {
+                start.get_or_insert(line_start);
+            }
+        }
+
+        self.offset = end;
+        let start = start?;
+        self.index += 1;
+
+        let text = contents[start..end].trim_end();
+        Some(Section { input: self.input, index: Some(self.index), text })
+    }
+}
+
+// Section parsers for Sections::next_as.
+pub fn lines<T>(section: &Section) -> Result<Vec<T>, InputError>
+where
+    T: FromStr,
+    T::Err: Into<Box<dyn Error>>,
+{
+    section.lines()
+}
+
+pub fn csv<T>(section: &Section) -> Result<Vec<T>, InputError>
+where
+    T: FromStr,
+    T::Err: Into<Box<dyn Error>>,
+{
+    section.csv()
+}
+
+pub fn csv_per_line<T>(section: &Section) -> Result<Vec<Vec<T>>, InputError>
+where
+    T: FromStr,
+    T::Err: Into<Box<dyn Error>>,
+{
+    section.csv_per_line()
+}
+
+pub fn grid(section: &Section) -> Result<Grid<char>, InputError> {
+    section.grid()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_root_finds_inputs() {
+        let path = input_path(9);
+        assert!(path.ends_with(Path::new("src").join("day9").join("input.txt")));
+        assert!(path.is_file());
+    }
+
+    #[test]
+    fn test_parse_config() {
+        assert_eq!(parse_config("input_root = /tmp/aoc\n"), Some(PathBuf::from("/tmp/aoc")));
+        assert_eq!(parse_config("# input_root = nope\ninput_root=\"inputs\""), Some(PathBuf::from("inputs")));
+        assert_eq!(parse_config("other = value"), None);
+    }
+
+    #[test]
+    fn test_missing_input_reports_path() {
+        let error = Input::for_day(42).unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains(&input_path(42).display().to_string()), "{}", message);
+    }
+
+    fn inline(contents: &str) -> Input {
+        Input::inline(contents)
+    }
+
+    #[test]
+    fn test_parsers_share_contents() {
+        let input = inline("1,2,3\n4,5,6\n");
+        let copy = input.clone();
+
+        assert_eq!(input.csv_per_line::<u8>().unwrap(), vec![vec![1, 2, 3], vec![4, 5, 6]]);
+        assert_eq!(copy.lines::<String>().unwrap(), vec!["1,2,3", "4,5,6"]);
+        assert!(Arc::ptr_eq(&input.contents, &copy.contents));
+    }
+
+    #[test]
+    fn test_csv_and_chars() {
+        let input = inline("1, -2 ,3\n");
+
+        assert_eq!(input.csv::<i64>().unwrap(), vec![1, -2, 3]);
+        assert_eq!(input.chars(), vec!['1', ',', ' ', '-', '2', ' ', ',', '3']);
+    }
+
+    #[test]
+    fn test_grid() {
+        let grid = inline("#.\n.#\n").grid().unwrap();
+        assert_eq!(grid.rows().collect::<Vec<_>>(), vec![&['#', '.'], &['.', '#']]);
+
+        let err = inline("#.\n.#.\n").grid().unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+    }
+
+    #[test]
+    fn test_closures_capture_state() {
+        let mut seen = 0;
+        let doubled = inline("1\n2\n3").lines_with(|s| {
+            seen += 1;
+            s.parse::<u32>().map(|v| v * 2)
+        });
+
+        assert_eq!(doubled.unwrap(), vec![2, 4, 6]);
+        assert_eq!(seen, 3);
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let input = Input::from_reader("3\n4\n".as_bytes()).unwrap().with_day(1);
+
+        assert_eq!(input.lines::<u32>().unwrap(), vec![3, 4]);
+        assert_eq!(input.path(), None);
+    }
+
+    #[test]
+    fn test_from_file() {
+        let input = Input::from_file(input_path(2)).unwrap();
+
+        assert_eq!(input.csv::<i64>().unwrap()[..4], [1, 0, 0, 3]);
+        assert_eq!(input.path(), Some(input_path(2).as_path()));
+        assert!(Input::from_file(input_root().join("missing.txt")).is_err());
+    }
+
+    #[test]
+    fn test_examples_are_discovered() {
+        let found = examples(3).unwrap();
+
+        assert_eq!(found.iter().map(|e| e.name.as_str()).collect::<Vec<&str>>(), vec!["example1", "example2"]);
+        assert_eq!(found[0].expected.part(1), Some("159"));
+        assert_eq!(found[1].expected.part(2), Some("410"));
+        assert_eq!(examples(2).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_example_input() {
+        let input = Input::example(6, "example1").unwrap();
+
+        assert_eq!(input.lines::<String>().unwrap()[0], "COM)00B");
+        assert_eq!(example(6, "example2").unwrap().expected, ExpectedAnswers { part1: None, part2: Some("4".to_string()) });
+        assert!(Input::example(6, "example9").is_err());
+    }
+
+    #[test]
+    fn test_expected_answers() {
+        assert!(ExpectedAnswers::parse("part3: 1").is_err());
+        assert!(ExpectedAnswers::parse("42").is_err());
+        assert_eq!(ExpectedAnswers::parse("\npart2:  CJZLP \n").unwrap().part(2), Some("CJZLP"));
+    }
+
+    #[test]
+    fn test_parse_errors_propagate() {
+        assert!(inline("1\nx\n").lines::<u32>().is_err());
+    }
+
+    #[test]
+    fn test_error_location_in_lines() {
+        let error = inline("12\n  4x5\n6").lines::<u32>().unwrap_err();
+
+        assert_eq!((error.line, error.column, error.field), (2, 3, None));
+        assert_eq!(error.token, "4x5");
+        assert_eq!(error.line_text, "  4x5");
+    }
+
+    #[test]
+    fn test_error_location_in_csv() {
+        let error = inline("1,2\n3, 4,x,6").csv_per_line::<u32>().unwrap_err();
+
+        assert_eq!((error.line, error.column, error.field), (2, 6, Some(2)));
+        assert_eq!(error.token, "x");
+
+        let error = inline("1,2,,4").csv::<u32>().unwrap_err();
+        assert_eq!((error.line, error.column, error.field), (1, 5, Some(2)));
+    }
+
+    #[test]
+    fn test_error_column_counts_characters() {
+        let error = inline("1\né1,x").csv_per_line_with(|s| s.trim_start_matches('é').parse::<u32>()).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 4));
+        assert!(error.to_string().ends_with("\n  |    ^"));
+    }
+
+    #[test]
+    fn test_error_snippet() {
+        let mut input = inline("R75,D30\nU62,Rx6,U55");
+        input.day = Some(3);
+        input.path = Some(PathBuf::from("day3/input.txt"));
+
+        let rows = input.csv_per_line::<String>().unwrap();
+        assert_eq!(rows.len(), 2);
+
+        let error = input.csv_per_line_with(|s| s[1..].parse::<u32>()).unwrap_err();
+        assert_eq!(error.to_string(), [
+            "day 3, day3/input.txt:2:5 (field 1): couldn't parse 'Rx6': invalid digit found in string",
+            "  |",
+            "2 | U62,Rx6,U55",
+            "  |     ^^^",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn test_sections() {
+        let input = inline("\n1,2,3\n\n\nab\ncd\n  \nx\ny\n\n");
+        let mut sections = input.sections();
+
+        assert_eq!(sections.next_as(csv::<i64>).unwrap(), vec![1, 2, 3]);
+        assert_eq!(sections.next_as(grid).unwrap().to_string(), "ab\ncd\n");
+        assert_eq!(sections.next_as(lines::<char>).unwrap(), vec!['x', 'y']);
+        assert!(sections.finish().is_ok());
+
+        let texts = input.sections().map(|s| (s.index(), s.text())).collect::<Vec<_>>();
+        assert_eq!(texts, vec![(Some(1), "1,2,3"), (Some(2), "ab\ncd"), (Some(3), "x\ny")]);
+    }
+
+    #[test]
+    fn test_section_errors() {
+        let input = inline("1,2\n\nR1\nQ2\n");
+
+        let mut sections = input.sections();
+        sections.next_as(csv::<i64>).unwrap();
+        let error = sections.next_as(|s| s.lines_with(|l| if l.starts_with('R') { Ok(l.len()) } else { Err("bad rule") })).unwrap_err();
+        assert_eq!((error.section, error.line, error.column), (Some(2), 4, 1));
+        assert!(error.to_string().starts_with("section 2, line 4, column 1: couldn't parse 'Q2'"), "{}", error);
+
+        let mut sections = input.sections();
+        sections.next();
+        sections.next();
+        let error = sections.next_as(csv::<i64>).unwrap_err();
+        assert_eq!(error.section, Some(3));
+        assert!(error.to_string().contains("Expected section 3 but the input has only 2"), "{}", error);
+
+        let mut sections = input.sections();
+        sections.next();
+        let error = sections.finish().unwrap_err();
+        assert_eq!((error.section, error.line), (Some(2), 3));
+    }
 }
@@ -15,7 +15,7 @@ pub enum IntcodeState {
 impl Intcode {
     pub fn create(tape: Vec<i32>) -> Intcode {
         Intcode {
-            tape: tape,
+            tape,
             input: VecDeque::new(),
             output: VecDeque::new(),
             instruction_pointer: 0
@@ -200,17 +200,9 @@ enum ParameterMode {
 
 impl ParameterMode {
     fn parse(opcode: i32, at_position: usize) -> Option<ParameterMode> {
-        let string = (opcode / 100).to_string();
-
-        let chars = string.chars().rev().collect::<Vec<char>>();
-
-        if at_position > chars.len() {
-            return Some(ParameterMode::Position);
-        }
-
-        match chars[at_position - 1] {
-            '0' => Some(ParameterMode::Position),
-            '1' => Some(ParameterMode::Immediate),
+        match (opcode / 10_i32.pow(at_position as u32 + 1)) % 10 {
+            0 => Some(ParameterMode::Position),
+            1 => Some(ParameterMode::Immediate),
             _ => None,
         }
     }
@@ -1,184 +1,411 @@
+use std::sync::Arc;
 use std::thread;
 use crossbeam_channel::{ Sender, Receiver, unbounded };
 use bus::Bus;
+use super::intcode_transcript::{ Transcript, Event };
+use super::intcode_frames::{ Frame, FrameReader };
 
 pub struct Intcode8086 {
   instruction_pointer: usize,
-  relative_base_pointer: usize,
-  von_neumann_tape: Vec<i64>,
-  input_sender: Sender<i64>,
+  relative_base_pointer: i64,
+  // Shared until the first write, so machines started from one program don't each copy it.
+  von_neumann_tape: Arc<Vec<i64>>,
+  input_sender: Option<Sender<i64>>,
   input_receiver: Receiver<i64>,
-  output_bus: Bus<i64>
+  output_bus: Bus<i64>,
+  instructions_executed: u64,
+  instruction_limit: Option<u64>,
+  end_of_input: EndOfInput,
+  transcript: Option<Transcript>,
+  tracer: Option<Tracer>
 }
 
+// The machine as it stood just before executing one instruction.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct TraceStep {
+  pub step: u64,
+  pub ip: usize,
+  pub relative_base: i64,
+  // The instruction word followed by its parameters.
+  pub words: Vec<i64>
+}
+
+impl std::fmt::Display for TraceStep {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let words = self.words.iter().map(|w| w.to_string()).collect::<Vec<_>>().join(",");
+    write!(f, "{} ip={} rb={} {}", self.step, self.ip, self.relative_base, words)
+  }
+}
+
+pub type Tracer = Box<dyn FnMut(&TraceStep) + Send>;
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum RunOutcome {
+  Halted,
+  FellOffTape { ip: usize },
+  InputExhausted,
+  LimitReached,
+  Faulted(IntcodeError)
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum IntcodeError {
+  UnknownOpcode { ip: usize, opcode: i64 },
+  UnknownParameterMode { ip: usize, opcode: i64 },
+  NegativeAddress { ip: usize, address: i64 },
+  Overflow { ip: usize }
+}
+
+pub enum EndOfInput {
+  Stop,
+  Default(i64),
+  Fallback(Box<dyn FnMut() -> Option<i64> + Send>)
+}
+
+impl std::fmt::Debug for EndOfInput {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      EndOfInput::Stop => write!(f, "Stop"),
+      EndOfInput::Default(value) => write!(f, "Default({})", value),
+      EndOfInput::Fallback(_) => write!(f, "Fallback")
+    }
+  }
+}
+
+impl std::fmt::Display for IntcodeError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      IntcodeError::UnknownOpcode { ip, opcode } => write!(f, "Unknown opcode {} at address {}", opcode, ip),
+      IntcodeError::UnknownParameterMode { ip, opcode } => write!(f, "Unknown parameter mode in opcode {} at address {}", opcode, ip),
+      IntcodeError::NegativeAddress { ip, address } => write!(f, "Negative address {} referenced at address {}", address, ip),
+      IntcodeError::Overflow { ip } => write!(f, "Arithmetic overflow at address {}", ip)
+    }
+  }
+}
+
+impl std::error::Error for IntcodeError {}
+
+impl From<IntcodeError> for RunOutcome {
+  fn from(error: IntcodeError) -> Self {
+    RunOutcome::Faulted(error)
+  }
+}
+
+const OUTPUT_CAPACITY: usize = 100;
+
 impl Intcode8086 {
   pub fn initialize(von_neumann_tape: Vec<i64>) -> Intcode8086 {
+    Intcode8086::from_shared(Arc::new(von_neumann_tape))
+  }
+
+  pub fn from_shared(von_neumann_tape: Arc<Vec<i64>>) -> Intcode8086 {
     let (i_s, i_r) = unbounded();
 
     Intcode8086 {
       instruction_pointer: 0,
       relative_base_pointer: 0,
-      von_neumann_tape: von_neumann_tape,
-      input_sender: i_s,
+      von_neumann_tape,
+      input_sender: Some(i_s),
       input_receiver: i_r,
-      output_bus: Bus::new(100)
+      output_bus: Bus::new(OUTPUT_CAPACITY),
+      instructions_executed: 0,
+      instruction_limit: None,
+      end_of_input: EndOfInput::Stop,
+      transcript: None,
+      tracer: None
     }
   }
 
-  pub fn get_input_port(&self) -> Sender<i64> {
-    self.input_sender.clone()
+  pub fn get_input_port(&mut self) -> Sender<i64> {
+    // The machine drops its own sender when it starts running, so that closing every port
+    // ends the input stream. Asking for a port afterwards opens a fresh channel that
+    // carries over anything still buffered.
+    if self.input_sender.is_none() {
+      let (i_s, i_r) = unbounded();
+      for pending in self.input_receiver.try_iter() {
+        i_s.send(pending).expect("The receiver is held locally");
+      }
+
+      self.input_receiver = i_r;
+      self.input_sender = Some(i_s);
+    }
+
+    self.input_sender.as_ref().unwrap().clone()
   }
 
   pub fn get_output_port(&mut self) -> bus::BusReader<i64> {
     self.output_bus.add_rx()
   }
 
-  pub fn process(mut self) -> std::thread::JoinHandle<Self> {
+  pub fn get_frame_port<F: Frame>(&mut self) -> FrameReader<F> {
+    FrameReader::new(self.get_output_port())
+  }
+
+  pub fn set_instruction_limit(&mut self, limit: u64) {
+    self.instruction_limit = Some(limit);
+  }
+
+  pub fn set_end_of_input(&mut self, policy: EndOfInput) {
+    self.end_of_input = policy;
+  }
+
+  pub fn start_recording(&mut self) {
+    self.transcript = Some(Transcript::default());
+  }
+
+  pub fn take_transcript(&mut self) -> Option<Transcript> {
+    self.transcript.take()
+  }
+
+  // Called with every instruction that decodes, before it runs.
+  pub fn set_tracer(&mut self, tracer: Tracer) {
+    self.tracer = Some(tracer);
+  }
+
+  pub fn instructions_executed(&self) -> u64 {
+    self.instructions_executed
+  }
+
+  pub fn process(mut self) -> std::thread::JoinHandle<(Self, RunOutcome)> {
     thread::spawn(move || {
-      while self.instruction_pointer < self.von_neumann_tape.len() {
-        let instruction = Intcode8086::decode_instruction(self.von_neumann_tape[self.instruction_pointer] as usize);
-
-        let res = match instruction {
-          Some(Instruction::Add(arg1, arg2, arg3)) => self.three_arg_fn(arg1, arg2, |a, b| a + b, arg3),
-          Some(Instruction::Multiply(arg1, arg2, arg3)) => self.three_arg_fn(arg1, arg2, |a, b| a * b, arg3),
-          Some(Instruction::StoreInput(arg1)) => self.store_input(arg1),
-          Some(Instruction::WriteOutput(arg1)) => self.write_output(arg1),
-          Some(Instruction::JumpIfTrue(arg1, arg2)) => self.jump(arg1, arg2, true),
-          Some(Instruction::JumpIfFalse(arg1, arg2)) => self.jump(arg1, arg2, false),
-          Some(Instruction::LessThan(arg1, arg2, arg3)) => self.compare_args(arg1, arg2, |a, b| a < b, arg3),
-          Some(Instruction::Equals(arg1, arg2, arg3)) => self.compare_args(arg1, arg2, |a, b| a == b, arg3),
-          Some(Instruction::AdjustRelativeBase(arg1)) => self.adjust_relative_base(arg1),
-          Some(Instruction::Halt) => InstructionResult {
-            next_instruction_pointer: None,
-            store: None,
-          },
-          None => panic!("Unknown instruction")
-        };
-
-        match res.next_instruction_pointer {
-          Some(x) => self.instruction_pointer = x,
-          None => break,
-        };
-
-        if let Some(store) = res.store {
-          if store.address >= self.von_neumann_tape.len() {
-            for i in self.von_neumann_tape.len()..=store.address {
-              self.von_neumann_tape.push(0);
-            }
-          }
-
-          self.von_neumann_tape[store.address] = store.value;
+      let outcome = self.run();
+      (self, outcome)
+    })
+  }
+
+  // A machine that can't go any further closes its output port, so readers see the end of
+  // output without waiting for the machine itself to be dropped. Ports asked for afterwards
+  // belong to a fresh bus.
+  pub fn run(&mut self) -> RunOutcome {
+    let outcome = self.execute();
+
+    match outcome {
+      RunOutcome::Halted | RunOutcome::FellOffTape { .. } | RunOutcome::Faulted(_) => self.output_bus = Bus::new(OUTPUT_CAPACITY),
+      RunOutcome::InputExhausted | RunOutcome::LimitReached => {}
+    }
+
+    outcome
+  }
+
+  fn execute(&mut self) -> RunOutcome {
+    self.input_sender = None;
+
+    loop {
+      if self.instruction_pointer >= self.von_neumann_tape.len() {
+        return RunOutcome::FellOffTape { ip: self.instruction_pointer };
+      }
+
+      if let Some(limit) = self.instruction_limit {
+        if self.instructions_executed >= limit {
+          return RunOutcome::LimitReached;
         }
       }
 
-      self
-    })
+      let res = match self.step() {
+        Ok(res) => res,
+        Err(outcome) => return outcome
+      };
+
+      self.instructions_executed += 1;
+
+      if let Some(store) = res.store {
+        self.set_memory_at(store.address, store.value);
+      }
+
+      match res.next_instruction_pointer {
+        Some(x) => self.instruction_pointer = x,
+        None => return RunOutcome::Halted,
+      };
+    }
+  }
+
+  fn step(&mut self) -> Result<InstructionResult, RunOutcome> {
+    let instruction = self.decode_instruction()?;
+
+    if self.tracer.is_some() {
+      let step = TraceStep {
+        step: self.instructions_executed,
+        ip: self.instruction_pointer,
+        relative_base: self.relative_base_pointer,
+        words: self.memory_slice(self.instruction_pointer..self.instruction_pointer + instruction.len())
+      };
+
+      if let Some(tracer) = &mut self.tracer {
+        tracer(&step);
+      }
+    }
+
+    match instruction {
+      Instruction::Add(arg1, arg2, arg3) => self.three_arg_fn(arg1, arg2, i64::checked_add, arg3),
+      Instruction::Multiply(arg1, arg2, arg3) => self.three_arg_fn(arg1, arg2, i64::checked_mul, arg3),
+      Instruction::StoreInput(arg1) => self.store_input(arg1),
+      Instruction::WriteOutput(arg1) => self.write_output(arg1),
+      Instruction::JumpIfTrue(arg1, arg2) => self.jump(arg1, arg2, true),
+      Instruction::JumpIfFalse(arg1, arg2) => self.jump(arg1, arg2, false),
+      Instruction::LessThan(arg1, arg2, arg3) => self.compare_args(arg1, arg2, |a, b| a < b, arg3),
+      Instruction::Equals(arg1, arg2, arg3) => self.compare_args(arg1, arg2, |a, b| a == b, arg3),
+      Instruction::AdjustRelativeBase(arg1) => self.adjust_relative_base(arg1),
+      Instruction::Halt => Ok(InstructionResult {
+        next_instruction_pointer: None,
+        store: None,
+      })
+    }
   }
 
   pub fn get_memory_at(&self, position: usize) -> i64 {
-    self.von_neumann_tape[position]
-  }
-
-  fn decode_instruction(opcode: usize) -> Option<Instruction> {
-    match opcode % 100 {
-      1 => { let p = ParameterMode::parse(opcode, 3); Some(Instruction::Add(p[0], p[1], p[2])) }
-      2 => { let p = ParameterMode::parse(opcode, 3); Some(Instruction::Multiply(p[0], p[1], p[2])) }
-      3 => { let p = ParameterMode::parse(opcode, 1); Some(Instruction::StoreInput(p[0])) },
-      4 => { let p = ParameterMode::parse(opcode, 1); Some(Instruction::WriteOutput(p[0])) },
-      5 => { let p = ParameterMode::parse(opcode, 2); Some(Instruction::JumpIfTrue(p[0], p[1])) },
-      6 => { let p = ParameterMode::parse(opcode, 2); Some(Instruction::JumpIfFalse(p[0], p[1])) },
-      7 => { let p = ParameterMode::parse(opcode, 3); Some(Instruction::LessThan(p[0], p[1], p[2])) },
-      8 => { let p = ParameterMode::parse(opcode, 3); Some(Instruction::Equals(p[0], p[1], p[2])) },
-      9 => { let p = ParameterMode::parse(opcode, 1); Some(Instruction::AdjustRelativeBase(p[0])) }
-      99 => Some(Instruction::Halt),
-      _ => None,
+    ParameterMode::read(self, position)
+  }
+
+  pub fn set_memory_at(&mut self, position: usize, value: i64) {
+    let tape = Arc::make_mut(&mut self.von_neumann_tape);
+    if position >= tape.len() {
+      tape.resize(position + 1, 0);
     }
+
+    tape[position] = value;
+  }
+
+  pub fn patch(&mut self, range: std::ops::Range<usize>, values: &[i64]) {
+    assert_eq!(range.len(), values.len(), "Patch range and values must be the same length");
+
+    let tape = Arc::make_mut(&mut self.von_neumann_tape);
+    if range.end > tape.len() {
+      tape.resize(range.end, 0);
+    }
+
+    tape[range].copy_from_slice(values);
+  }
+
+  pub fn memory_slice(&self, range: std::ops::Range<usize>) -> Vec<i64> {
+    range.map(|position| self.get_memory_at(position)).collect()
+  }
+
+  pub fn memory_len(&self) -> usize {
+    self.von_neumann_tape.len()
+  }
+
+  fn decode_instruction(&self) -> Result<Instruction, IntcodeError> {
+    let opcode = self.von_neumann_tape[self.instruction_pointer];
+    let modes = |count| ParameterMode::parse(opcode as usize, count)
+      .ok_or(IntcodeError::UnknownParameterMode { ip: self.instruction_pointer, opcode });
+
+    let instruction = match opcode % 100 {
+      1 => { let p = modes(3)?; Instruction::Add(p[0], p[1], p[2]) }
+      2 => { let p = modes(3)?; Instruction::Multiply(p[0], p[1], p[2]) }
+      3 => { let p = modes(1)?; Instruction::StoreInput(p[0]) },
+      4 => { let p = modes(1)?; Instruction::WriteOutput(p[0]) },
+      5 => { let p = modes(2)?; Instruction::JumpIfTrue(p[0], p[1]) },
+      6 => { let p = modes(2)?; Instruction::JumpIfFalse(p[0], p[1]) },
+      7 => { let p = modes(3)?; Instruction::LessThan(p[0], p[1], p[2]) },
+      8 => { let p = modes(3)?; Instruction::Equals(p[0], p[1], p[2]) },
+      9 => { let p = modes(1)?; Instruction::AdjustRelativeBase(p[0]) }
+      99 => Instruction::Halt,
+      _ => return Err(IntcodeError::UnknownOpcode { ip: self.instruction_pointer, opcode }),
+    };
+
+    Ok(instruction)
   }
 
-  fn three_arg_fn(&self, arg1: ParameterMode, arg2: ParameterMode, func: fn(i64, i64) -> i64, arg3: ParameterMode) -> InstructionResult {
-    let store_address: usize = arg3.set(self, 3);
-    let store_value = func(arg1.get(self, 1), arg2.get(self, 2));
+  fn three_arg_fn(&self, arg1: ParameterMode, arg2: ParameterMode, func: fn(i64, i64) -> Option<i64>, arg3: ParameterMode) -> Result<InstructionResult, RunOutcome> {
+    let store_address: usize = arg3.set(self, 3)?;
+    let store_value = func(arg1.get(self, 1)?, arg2.get(self, 2)?)
+      .ok_or(IntcodeError::Overflow { ip: self.instruction_pointer })?;
 
-    InstructionResult {
+    Ok(InstructionResult {
         next_instruction_pointer: Some(self.instruction_pointer + 4),
         store: Some(StoreInstruction {
             address: store_address,
             value: store_value,
         }),
-    }
+    })
   }
 
-  fn jump(&self, arg1: ParameterMode, arg2: ParameterMode, jump_if: bool) -> InstructionResult {
-    let eval = arg1.get(self, 1);
-    let next = match jump_if {
-      true => {
-        if eval != 0 {
-          arg2.get(self, 2) as usize
-        } else {
-          self.instruction_pointer + 3
-        }
-      }
-      false => {
-        if eval == 0 {
-          arg2.get(self, 2) as usize
-        } else {
-          self.instruction_pointer + 3
-        }
+  fn jump(&self, arg1: ParameterMode, arg2: ParameterMode, jump_if: bool) -> Result<InstructionResult, RunOutcome> {
+    let eval = arg1.get(self, 1)?;
+    let next = if (eval != 0) == jump_if {
+      let target = arg2.get(self, 2)?;
+
+      if target < 0 {
+        return Err(IntcodeError::NegativeAddress { ip: self.instruction_pointer, address: target }.into());
       }
+
+      target as usize
+    } else {
+      self.instruction_pointer + 3
     };
 
-    InstructionResult {
+    Ok(InstructionResult {
       next_instruction_pointer: Some(next),
       store: None,
-    }
+    })
   }
 
-  fn compare_args(&self, arg1: ParameterMode, arg2: ParameterMode, func: fn(i64, i64) -> bool, arg3: ParameterMode) -> InstructionResult {
-    let store_address: usize = arg3.set(self, 3);
-    let result = func(arg1.get(self, 1), arg2.get(self, 2));
+  fn compare_args(&self, arg1: ParameterMode, arg2: ParameterMode, func: fn(i64, i64) -> bool, arg3: ParameterMode) -> Result<InstructionResult, RunOutcome> {
+    let store_address: usize = arg3.set(self, 3)?;
+    let result = func(arg1.get(self, 1)?, arg2.get(self, 2)?);
 
-    InstructionResult {
+    Ok(InstructionResult {
       next_instruction_pointer: Some(self.instruction_pointer + 4),
       store: Some(StoreInstruction {
           address: store_address,
           value: if result { 1 } else { 0 },
       }),
-    }
+    })
   }
 
-  fn store_input(&mut self, arg1: ParameterMode) -> InstructionResult {
-    let address = arg1.set(self, 1);
-    
-    if address >= self.von_neumann_tape.len() {
-      for i in self.von_neumann_tape.len()..=address {
-        self.von_neumann_tape.push(0);
-      }
+  fn store_input(&mut self, arg1: ParameterMode) -> Result<InstructionResult, RunOutcome> {
+    let address = arg1.set(self, 1)?;
+    let value = match self.input_receiver.recv() {
+      Ok(value) => value,
+      Err(_) => self.next_after_end_of_input()?
+    };
+
+    if let Some(transcript) = &mut self.transcript {
+      transcript.push(Event::Input { step: self.instructions_executed, value });
     }
 
-    InstructionResult {
+    Ok(InstructionResult {
       next_instruction_pointer: Some(self.instruction_pointer + 2),
       store: Some(StoreInstruction {
-        address: address,
-        value: self.input_receiver.recv().unwrap()
+        address,
+        value
       })
-    }    
+    })
+  }
+
+  fn next_after_end_of_input(&mut self) -> Result<i64, RunOutcome> {
+    let value = match &mut self.end_of_input {
+      EndOfInput::Stop => None,
+      EndOfInput::Default(value) => Some(*value),
+      EndOfInput::Fallback(provider) => provider()
+    };
+
+    value.ok_or(RunOutcome::InputExhausted)
   }
 
-  fn write_output(&mut self, arg1: ParameterMode) -> InstructionResult {
-    self.output_bus.broadcast(arg1.get(self, 1));
-    InstructionResult {
+  fn write_output(&mut self, arg1: ParameterMode) -> Result<InstructionResult, RunOutcome> {
+    let value = arg1.get(self, 1)?;
+
+    if let Some(transcript) = &mut self.transcript {
+      transcript.push(Event::Output { step: self.instructions_executed, value });
+    }
+
+    self.output_bus.broadcast(value);
+
+    Ok(InstructionResult {
       next_instruction_pointer: Some(self.instruction_pointer + 2),
       store: None,
-    }
+    })
   }
 
-  fn adjust_relative_base(&mut self, arg1: ParameterMode) -> InstructionResult {
-    self.relative_base_pointer = (self.relative_base_pointer as i64 + arg1.get(self, 1)) as usize;
+  fn adjust_relative_base(&mut self, arg1: ParameterMode) -> Result<InstructionResult, RunOutcome> {
+    self.relative_base_pointer = self.relative_base_pointer.checked_add(arg1.get(self, 1)?)
+      .ok_or(IntcodeError::Overflow { ip: self.instruction_pointer })?;
 
-    InstructionResult {
+    Ok(InstructionResult {
       next_instruction_pointer: Some(self.instruction_pointer + 2),
       store: None,
-    }
+    })
   }
 }
 
@@ -196,6 +423,18 @@ enum Instruction {
   Halt
 }
 
+impl Instruction {
+  // The instruction word plus its parameters.
+  fn len(&self) -> usize {
+    match self {
+      Instruction::Add(..) | Instruction::Multiply(..) | Instruction::LessThan(..) | Instruction::Equals(..) => 4,
+      Instruction::JumpIfTrue(..) | Instruction::JumpIfFalse(..) => 3,
+      Instruction::StoreInput(_) | Instruction::WriteOutput(_) | Instruction::AdjustRelativeBase(_) => 2,
+      Instruction::Halt => 1
+    }
+  }
+}
+
 #[derive(PartialEq, Eq, Copy, Clone, Debug)]
 enum ParameterMode {
   Position,
@@ -214,57 +453,64 @@ impl std::fmt::Display for ParameterMode {
 }
 
 impl ParameterMode {
-  fn parse(opcode: usize, number_of_positions: usize) -> Vec<ParameterMode> {
-    let string = (opcode / 100).to_string();
+  // Modes are the decimal digits above the two opcode digits, lowest first. Digits past
+  // `number_of_positions` are left as Position without being checked.
+  fn parse(opcode: usize, number_of_positions: usize) -> Option<[ParameterMode; 3]> {
+    let mut res = [ParameterMode::Position; 3];
+    let mut digits = opcode / 100;
+
+    for mode in res.iter_mut().take(number_of_positions) {
+      *mode = match digits % 10 {
+        0 => ParameterMode::Position,
+        1 => ParameterMode::Immediate,
+        2 => ParameterMode::Relative,
+        _ => return None
+      };
 
-    let chars = string.chars().rev().collect::<Vec<char>>();
+      digits /= 10;
+    }
 
-    let mut res = Vec::new();
+    Some(res)
+  }
 
-    for at_position in 1..=number_of_positions {
-      if at_position > chars.len() {
-        res.push(ParameterMode::Position);
-        continue;
-      }
+  fn get(self, cpu: &Intcode8086, at_position: usize) -> Result<i64, IntcodeError> {
+    let addr = match self {
+      ParameterMode::Immediate => (cpu.instruction_pointer + at_position) as i64,
+      ParameterMode::Position => ParameterMode::read(cpu, cpu.instruction_pointer + at_position),
+      ParameterMode::Relative => ParameterMode::read(cpu, cpu.instruction_pointer + at_position).checked_add(cpu.relative_base_pointer)
+        .ok_or(IntcodeError::Overflow { ip: cpu.instruction_pointer })?
+    };
 
-      match chars[at_position - 1] {
-        '0' => res.push(ParameterMode::Position),
-        '1' => res.push(ParameterMode::Immediate),
-        '2' => res.push(ParameterMode::Relative),
-        _ => continue
-      };
+    if addr < 0 {
+      return Err(IntcodeError::NegativeAddress { ip: cpu.instruction_pointer, address: addr });
     }
 
-    res
+    Ok(ParameterMode::read(cpu, addr as usize))
   }
 
-  fn get(&self, cpu: &Intcode8086, at_position: usize) -> i64 {
+  fn set(self, cpu: &Intcode8086, at_position: usize) -> Result<usize, IntcodeError> {
     let addr = match self {
-      ParameterMode::Immediate => cpu.instruction_pointer + at_position,
-      ParameterMode::Position => cpu.von_neumann_tape[cpu.instruction_pointer + at_position] as usize,
-      ParameterMode::Relative => (cpu.von_neumann_tape[cpu.instruction_pointer + at_position] + cpu.relative_base_pointer as i64) as usize
+      ParameterMode::Relative => cpu.relative_base_pointer.checked_add(ParameterMode::read(cpu, cpu.instruction_pointer + at_position))
+        .ok_or(IntcodeError::Overflow { ip: cpu.instruction_pointer })?,
+      _ => ParameterMode::read(cpu, cpu.instruction_pointer + at_position)
     };
 
-    if addr >= cpu.von_neumann_tape.len() {
-      0      
-    } else {
-      cpu.von_neumann_tape[addr]
+    if addr < 0 {
+      return Err(IntcodeError::NegativeAddress { ip: cpu.instruction_pointer, address: addr });
     }
+
+    Ok(addr as usize)
   }
 
-  fn set(&self, cpu: &Intcode8086, at_position: usize) -> usize {
-    match self {
-      ParameterMode::Relative => (cpu.relative_base_pointer as i64 + cpu.von_neumann_tape[cpu.instruction_pointer + at_position]) as usize,
-      _ => cpu.von_neumann_tape[cpu.instruction_pointer + at_position] as usize
+  fn read(cpu: &Intcode8086, addr: usize) -> i64 {
+    if addr >= cpu.von_neumann_tape.len() {
+      0
+    } else {
+      cpu.von_neumann_tape[addr]
     }
   }
 }
 
-struct InstructionArgument {
-  mode: ParameterMode,
-  value: i64
-}
-
 struct InstructionResult {
   next_instruction_pointer: Option<usize>,
   store: Option<StoreInstruction>,
@@ -290,27 +536,33 @@ mod tests {
   #[test]
   fn test_parsing_instructions() {
     let cpu = Intcode8086::initialize(parse_csv("1,0,0,0,99"));
-    let cpu = cpu.process().join().unwrap();
+    let (cpu, outcome) = cpu.process().join().unwrap();
+    assert_eq!(outcome, RunOutcome::Halted);
     assert_eq!(cpu.get_memory_at(0), 2);
 
     let cpu = Intcode8086::initialize(parse_csv("2,3,0,3,99"));
-    let cpu = cpu.process().join().unwrap();
+    let (cpu, outcome) = cpu.process().join().unwrap();
+    assert_eq!(outcome, RunOutcome::Halted);
     assert_eq!(cpu.get_memory_at(0), 2);
 
     let cpu = Intcode8086::initialize(parse_csv("2,4,4,5,99,0"));
-    let cpu = cpu.process().join().unwrap();
+    let (cpu, outcome) = cpu.process().join().unwrap();
+    assert_eq!(outcome, RunOutcome::Halted);
     assert_eq!(cpu.get_memory_at(0), 2);
 
     let cpu = Intcode8086::initialize(parse_csv("1,1,1,4,99,5,6,0,99"));
-    let cpu = cpu.process().join().unwrap();
+    let (cpu, outcome) = cpu.process().join().unwrap();
+    assert_eq!(outcome, RunOutcome::Halted);
     assert_eq!(cpu.get_memory_at(0), 30);
 
     let cpu = Intcode8086::initialize(parse_csv("1002,4,3,4,33"));
-    let cpu = cpu.process().join().unwrap();
+    let (cpu, outcome) = cpu.process().join().unwrap();
+    assert_eq!(outcome, RunOutcome::Halted);
     assert_eq!(cpu.get_memory_at(4), 99); // pos 4
 
     let cpu = Intcode8086::initialize(parse_csv("1101,100,-1,4,0"));
-    let cpu = cpu.process().join().unwrap();
+    let (cpu, outcome) = cpu.process().join().unwrap();
+    assert_eq!(outcome, RunOutcome::Halted);
     assert_eq!(cpu.get_memory_at(4), 99); // pos 4
   }
 
@@ -446,8 +698,8 @@ mod tests {
 
   #[test]
   fn test_day9_relative_base() {
-    let mut cpu = Intcode8086::initialize(parse_csv("109,2000,109,19,99"));
-    cpu = cpu.process().join().expect("");
+    let cpu = Intcode8086::initialize(parse_csv("109,2000,109,19,99"));
+    let (cpu, _) = cpu.process().join().expect("");
     assert_eq!(cpu.relative_base_pointer, 2019);
   }
 
@@ -506,9 +758,194 @@ mod tests {
 
   #[test]
   fn test_parsing_parameter_mode() {
-    let pos = ParameterMode::parse(1002, 3);
+    let pos = ParameterMode::parse(1002, 3).unwrap();
     assert_eq!(ParameterMode::Position, pos[0]);
     assert_eq!(ParameterMode::Immediate, pos[1]);
     assert_eq!(ParameterMode::Position, pos[2]);
+
+    assert_eq!(ParameterMode::parse(1302, 3), None);
+    assert_eq!(ParameterMode::parse(21205, 2), Some([ParameterMode::Relative, ParameterMode::Immediate, ParameterMode::Position]));
+    assert_eq!(ParameterMode::parse(30004, 1), Some([ParameterMode::Position; 3]));
+  }
+
+  #[test]
+  fn test_memory_access_before_run() {
+    let mut cpu = Intcode8086::initialize(parse_csv("1,0,0,0,99"));
+    assert_eq!(cpu.memory_len(), 5);
+    assert_eq!(cpu.get_memory_at(10), 0);
+
+    cpu.patch(1..3, &[4, 4]);
+    assert_eq!(cpu.memory_slice(0..6), vec![1, 4, 4, 0, 99, 0]);
+    assert_eq!(cpu.memory_len(), 5);
+
+    let (cpu, outcome) = cpu.process().join().unwrap();
+    assert_eq!(outcome, RunOutcome::Halted);
+    assert_eq!(cpu.get_memory_at(0), 198);
+  }
+
+  #[test]
+  fn test_memory_access_grows_tape() {
+    let mut cpu = Intcode8086::initialize(parse_csv("99"));
+    cpu.set_memory_at(3, 7);
+    assert_eq!(cpu.memory_slice(0..4), vec![99, 0, 0, 7]);
+
+    cpu.patch(5..7, &[1, 2]);
+    assert_eq!(cpu.memory_len(), 7);
+    assert_eq!(cpu.memory_slice(4..8), vec![0, 1, 2, 0]);
+  }
+
+  #[test]
+  fn test_memory_access_on_paused_machine() {
+    let mut cpu = Intcode8086::initialize(parse_csv("3,10,4,10,99"));
+    assert_eq!(cpu.run(), RunOutcome::InputExhausted);
+
+    cpu.set_memory_at(10, 42);
+    cpu.patch(0..2, &[4, 10]);
+    let mut io = cpu.get_output_port();
+    assert_eq!(cpu.run(), RunOutcome::Halted);
+    assert_eq!(io.recv().unwrap(), 42);
+    assert_eq!(io.recv().unwrap(), 42);
+  }
+
+  #[test]
+  fn test_shared_tape_is_copied_on_write() {
+    let tape = Arc::new(parse_csv("1,0,0,0,99"));
+    let mut cpu = Intcode8086::from_shared(tape.clone());
+    assert_eq!(Arc::strong_count(&tape), 2);
+
+    cpu.set_memory_at(1, 4);
+    assert_eq!(Arc::strong_count(&tape), 1);
+    assert_eq!(cpu.run(), RunOutcome::Halted);
+    assert_eq!(cpu.get_memory_at(0), 100);
+    assert_eq!(*tape, parse_csv("1,0,0,0,99"));
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_patch_length_mismatch() {
+    let mut cpu = Intcode8086::initialize(parse_csv("99"));
+    cpu.patch(0..2, &[1]);
+  }
+
+  #[test]
+  fn test_outcome_fell_off_tape() {
+    let mut cpu = Intcode8086::initialize(parse_csv("1,0,0,0"));
+    assert_eq!(cpu.run(), RunOutcome::FellOffTape { ip: 4 });
+    assert_eq!(cpu.get_memory_at(0), 2);
+  }
+
+  #[test]
+  fn test_outcome_input_exhausted() {
+    let mut cpu = Intcode8086::initialize(parse_csv("3,0,3,1,99"));
+    cpu.get_input_port().send(7).expect("Send should succeed");
+    assert_eq!(cpu.run(), RunOutcome::InputExhausted);
+    assert_eq!(cpu.get_memory_at(0), 7);
+    assert_eq!(cpu.get_memory_at(1), 0);
+  }
+
+  #[test]
+  fn test_resume_after_input_exhausted() {
+    let mut cpu = Intcode8086::initialize(parse_csv("3,0,3,1,99"));
+    cpu.get_input_port().send(7).expect("Send should succeed");
+    assert_eq!(cpu.run(), RunOutcome::InputExhausted);
+
+    cpu.get_input_port().send(8).expect("Send should succeed");
+    assert_eq!(cpu.run(), RunOutcome::Halted);
+    assert_eq!(cpu.get_memory_at(0), 7);
+    assert_eq!(cpu.get_memory_at(1), 8);
+  }
+
+  #[test]
+  fn test_end_of_input_default() {
+    let mut cpu = Intcode8086::initialize(parse_csv("3,0,3,1,99"));
+    cpu.set_end_of_input(EndOfInput::Default(-1));
+    cpu.get_input_port().send(7).expect("Send should succeed");
+    assert_eq!(cpu.run(), RunOutcome::Halted);
+    assert_eq!(cpu.get_memory_at(0), 7);
+    assert_eq!(cpu.get_memory_at(1), -1);
+  }
+
+  #[test]
+  fn test_end_of_input_fallback() {
+    let mut cpu = Intcode8086::initialize(parse_csv("3,0,3,1,3,2,99"));
+    let mut remaining = vec![20, 10];
+    cpu.set_end_of_input(EndOfInput::Fallback(Box::new(move || remaining.pop())));
+    assert_eq!(cpu.run(), RunOutcome::InputExhausted);
+    assert_eq!(cpu.get_memory_at(0), 10);
+    assert_eq!(cpu.get_memory_at(1), 20);
   }
-}
\ No newline at end of file
+
+  #[test]
+  fn test_input_ends_when_upstream_halts() {
+    let mut upstream = Intcode8086::initialize(parse_csv("104,5,99"));
+    let mut downstream = Intcode8086::initialize(parse_csv("3,9,1001,9,0,10,1105,1,0,0,0"));
+    let mut io = upstream.get_output_port();
+    let downstream_input = downstream.get_input_port();
+    let downstream_handle = downstream.process();
+
+    upstream.process().join().expect("");
+    for v in io.iter() {
+      downstream_input.send(v).expect("Send should succeed");
+    }
+    drop(downstream_input);
+
+    let (downstream, outcome) = downstream_handle.join().expect("");
+    assert_eq!(outcome, RunOutcome::InputExhausted);
+    assert_eq!(downstream.get_memory_at(10), 5);
+  }
+
+  #[test]
+  fn test_outcome_limit_reached() {
+    let mut cpu = Intcode8086::initialize(parse_csv("1105,1,0"));
+    cpu.set_instruction_limit(10);
+    assert_eq!(cpu.run(), RunOutcome::LimitReached);
+    assert_eq!(cpu.instructions_executed(), 10);
+
+    cpu.set_instruction_limit(15);
+    assert_eq!(cpu.run(), RunOutcome::LimitReached);
+    assert_eq!(cpu.instructions_executed(), 15);
+  }
+
+  #[test]
+  fn test_outcome_faulted() {
+    let mut cpu = Intcode8086::initialize(parse_csv("1,0,0,0,42"));
+    assert_eq!(cpu.run(), RunOutcome::Faulted(IntcodeError::UnknownOpcode { ip: 4, opcode: 42 }));
+
+    let mut cpu = Intcode8086::initialize(parse_csv("301,0,0,0,99"));
+    assert_eq!(cpu.run(), RunOutcome::Faulted(IntcodeError::UnknownParameterMode { ip: 0, opcode: 301 }));
+
+    let mut cpu = Intcode8086::initialize(parse_csv("4,-3,99"));
+    assert_eq!(cpu.run(), RunOutcome::Faulted(IntcodeError::NegativeAddress { ip: 0, address: -3 }));
+  }
+
+  #[test]
+  fn test_outcome_overflow() {
+    let mut cpu = Intcode8086::initialize(parse_csv("1101,9223372036854775807,1,0,99"));
+    assert_eq!(cpu.run(), RunOutcome::Faulted(IntcodeError::Overflow { ip: 0 }));
+
+    let mut cpu = Intcode8086::initialize(parse_csv("1101,0,0,0,1102,4611686018427387904,2,0,99"));
+    assert_eq!(cpu.run(), RunOutcome::Faulted(IntcodeError::Overflow { ip: 4 }));
+
+    let mut cpu = Intcode8086::initialize(parse_csv("109,9223372036854775807,109,1,99"));
+    assert_eq!(cpu.run(), RunOutcome::Faulted(IntcodeError::Overflow { ip: 2 }));
+
+    let mut cpu = Intcode8086::initialize(parse_csv("109,9223372036854775807,204,1,99"));
+    assert_eq!(cpu.run(), RunOutcome::Faulted(IntcodeError::Overflow { ip: 2 }));
+  }
+
+  #[test]
+  fn test_tracer() {
+    let steps = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = steps.clone();
+
+    let mut cpu = Intcode8086::initialize(parse_csv("109,5,1005,0,7,42,99,104,8,99"));
+    cpu.set_tracer(Box::new(move |step| seen.lock().unwrap().push(step.clone())));
+    assert_eq!(cpu.run(), RunOutcome::Halted);
+
+    let steps = steps.lock().unwrap();
+    assert_eq!(steps.iter().map(|s| s.ip).collect::<Vec<_>>(), vec![0, 2, 7, 9]);
+    assert_eq!(steps[1], TraceStep { step: 1, ip: 2, relative_base: 5, words: vec![1005, 0, 7] });
+    assert_eq!(steps[1].to_string(), "1 ip=2 rb=5 1005,0,7");
+    assert_eq!(steps[3].words, vec![99]);
+  }
+}
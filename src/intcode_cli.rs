@@ -0,0 +1,404 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::sync::{ Arc, Mutex };
+use std::thread;
+use crossbeam_channel::Sender;
+use super::inputhandling::Input;
+use super::intcode_8086::{ Intcode8086, RunOutcome, TraceStep };
+
+pub const USAGE: &str = "\
+usage: intcode [options] <program>
+
+options:
+  --input <a,b,c>  feed these values to the program instead of reading stdin
+  --ascii          feed stdin as character codes and print outputs as text
+  --trace          print every instruction to stderr as it runs
+  --profile        print instruction counts to stderr once the program stops
+  --limit <n>      stop after n instructions";
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Inputs {
+  Listed(Vec<i64>),
+  // Fed a line at a time as the program runs: numbers separated by commas or whitespace,
+  // or every character of the line, newline included, in ASCII mode.
+  Stdin
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum OutputMode {
+  Numbers,
+  Ascii
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Options {
+  pub program: PathBuf,
+  pub inputs: Inputs,
+  pub mode: OutputMode,
+  pub trace: bool,
+  pub profile: bool,
+  pub limit: Option<u64>
+}
+
+fn value<'a>(args: &'a [String], index: usize, flag: &str) -> Result<&'a str, String> {
+  args.get(index + 1).map(|s| s.as_str()).ok_or_else(|| format!("{} needs a value", flag))
+}
+
+// Returns None when help was asked for.
+pub fn parse_args(args: &[String]) -> Result<Option<Options>, String> {
+  let mut program = None;
+  let mut inputs = Inputs::Stdin;
+  let mut mode = OutputMode::Numbers;
+  let mut trace = false;
+  let mut profile = false;
+  let mut limit = None;
+
+  let mut index = 0;
+  while index < args.len() {
+    let arg = args[index].as_str();
+
+    match arg {
+      "--input" => { inputs = Inputs::Listed(parse_values(value(args, index, arg)?)?); index += 1; },
+      "--ascii" => mode = OutputMode::Ascii,
+      "--trace" => trace = true,
+      "--profile" => profile = true,
+      "--limit" => {
+        limit = Some(value(args, index, arg)?.parse::<u64>().map_err(|_| format!("--limit needs an instruction count, not '{}'", args[index + 1]))?);
+        index += 1;
+      },
+      "help" | "--help" | "-h" => return Ok(None),
+      other if other.starts_with("--") => return Err(format!("Unexpected argument '{}'", other)),
+      path => match program {
+        None => program = Some(PathBuf::from(path)),
+        Some(_) => return Err(format!("Only one program can be run, but '{}' was given as well", path))
+      }
+    }
+
+    index += 1;
+  }
+
+  match program {
+    Some(program) => Ok(Some(Options { program, inputs, mode, trace, profile, limit })),
+    None => Err("No program given".to_string())
+  }
+}
+
+pub fn parse_values(text: &str) -> Result<Vec<i64>, String> {
+  text.split(|c: char| c == ',' || c.is_whitespace())
+    .filter(|s| !s.is_empty())
+    .map(|s| s.parse::<i64>().map_err(|_| format!("'{}' isn't an Intcode value", s)))
+    .collect()
+}
+
+pub fn ascii_values(text: &str) -> Vec<i64> {
+  text.chars().map(|c| c as i64).collect()
+}
+
+pub fn load_program(options: &Options) -> Result<Vec<i64>, Box<dyn Error>> {
+  Ok(Input::from_file(&options.program)?.csv()?)
+}
+
+// Stops at the end of the input, a line that doesn't parse, or once the machine is gone.
+fn feed_lines<R: BufRead>(reader: R, mode: OutputMode, port: Sender<i64>) {
+  for line in reader.lines() {
+    let line = match line {
+      Ok(line) => line,
+      Err(e) => return eprintln!("Stopped reading stdin: {}", e)
+    };
+
+    let values = match mode {
+      OutputMode::Numbers => match parse_values(&line) {
+        Ok(values) => values,
+        Err(e) => return eprintln!("Stopped reading stdin: {}", e)
+      },
+      OutputMode::Ascii => ascii_values(&format!("{}\n", line))
+    };
+
+    if values.into_iter().any(|value| port.send(value).is_err()) {
+      return;
+    }
+  }
+}
+
+// How often each opcode and each instruction address was executed.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Profile {
+  pub opcodes: BTreeMap<i64, u64>,
+  pub addresses: BTreeMap<usize, u64>
+}
+
+fn opcode_name(opcode: i64) -> &'static str {
+  match opcode {
+    1 => "add",
+    2 => "multiply",
+    3 => "input",
+    4 => "output",
+    5 => "jump-if-true",
+    6 => "jump-if-false",
+    7 => "less-than",
+    8 => "equals",
+    9 => "adjust-base",
+    99 => "halt",
+    _ => "unknown"
+  }
+}
+
+impl Profile {
+  pub fn record(&mut self, step: &TraceStep) {
+    *self.opcodes.entry(step.words[0] % 100).or_insert(0) += 1;
+    *self.addresses.entry(step.ip).or_insert(0) += 1;
+  }
+
+  pub fn total(&self) -> u64 {
+    self.opcodes.values().sum()
+  }
+
+  // The most executed addresses, busiest first; ties go to the lower address.
+  pub fn hottest(&self, count: usize) -> Vec<(usize, u64)> {
+    let mut addresses = self.addresses.iter().map(|(a, n)| (*a, *n)).collect::<Vec<_>>();
+    addresses.sort_by_key(|(address, n)| (std::cmp::Reverse(*n), *address));
+    addresses.truncate(count);
+    addresses
+  }
+}
+
+impl std::fmt::Display for Profile {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let total = self.total();
+    writeln!(f, "{} instructions", total)?;
+
+    for (opcode, n) in &self.opcodes {
+      writeln!(f, "  {:<13} {:>10}  {:>5.1}%", opcode_name(*opcode), n, 100.0 * *n as f64 / total as f64)?;
+    }
+
+    writeln!(f, "hottest addresses")?;
+    for (address, n) in self.hottest(10) {
+      writeln!(f, "  {:>13} {:>10}", address, n)?;
+    }
+
+    Ok(())
+  }
+}
+
+pub struct Execution {
+  pub outputs: Vec<i64>,
+  pub outcome: RunOutcome,
+  pub instructions: u64,
+  pub profile: Option<Profile>
+}
+
+// Runs the program on its own thread, handing each output to `on_output` as soon as it's
+// produced. Traced instructions go straight to stderr as they run. The stdin reader is only
+// used when no inputs were listed.
+pub fn execute<R, F>(program: Vec<i64>, options: &Options, stdin: R, mut on_output: F) -> Execution
+  where R: BufRead + Send + 'static, F: FnMut(i64)
+{
+  let mut cpu = Intcode8086::initialize(program);
+  let profile = Arc::new(Mutex::new(Profile::default()));
+
+  if let Some(limit) = options.limit {
+    cpu.set_instruction_limit(limit);
+  }
+
+  if options.trace || options.profile {
+    let (trace, profiling, recorded) = (options.trace, options.profile, profile.clone());
+
+    cpu.set_tracer(Box::new(move |step| {
+      if trace {
+        eprintln!("{}", step);
+      }
+
+      if profiling {
+        recorded.lock().expect("Only the tracer holds the lock").record(step);
+      }
+    }));
+  }
+
+  let port = cpu.get_input_port();
+  match &options.inputs {
+    Inputs::Listed(values) => for value in values {
+      port.send(*value).expect("The machine holds the receiver");
+    },
+    // Not joined: it may still be waiting on stdin after the program has finished.
+    Inputs::Stdin => {
+      let mode = options.mode;
+      let port = port.clone();
+      thread::spawn(move || feed_lines(stdin, mode, port));
+    }
+  }
+  drop(port);
+
+  // The machine is dropped on its own thread so the output port closes however it stopped.
+  let mut output_port = cpu.get_output_port();
+  let machine = thread::spawn(move || {
+    let outcome = cpu.run();
+    (outcome, cpu.instructions_executed())
+  });
+
+  let mut outputs = Vec::new();
+  for value in output_port.iter() {
+    on_output(value);
+    outputs.push(value);
+  }
+
+  let (outcome, instructions) = machine.join().expect("The machine shouldn't panic");
+
+  let profile = match options.profile {
+    true => Some(Arc::try_unwrap(profile).expect("The machine has been dropped").into_inner().expect("The tracer didn't panic")),
+    false => None
+  };
+
+  Execution { outputs, outcome, instructions, profile }
+}
+
+// In ASCII mode anything that isn't a character is printed as a number on a line of its own.
+// A newline straight after such a number is already accounted for by that line.
+pub struct OutputFormatter {
+  mode: OutputMode,
+  at_line_start: bool,
+  after_number: bool
+}
+
+impl OutputFormatter {
+  pub fn new(mode: OutputMode) -> OutputFormatter {
+    OutputFormatter { mode, at_line_start: true, after_number: false }
+  }
+
+  pub fn format(&mut self, value: i64) -> String {
+    let character = self.mode == OutputMode::Ascii && (0..128).contains(&value);
+
+    let text = if character && value == 10 && self.after_number {
+      String::new()
+    } else if character {
+      (value as u8 as char).to_string()
+    } else if self.at_line_start {
+      format!("{}\n", value)
+    } else {
+      format!("\n{}\n", value)
+    };
+
+    if !text.is_empty() {
+      self.at_line_start = text.ends_with('\n');
+    }
+    self.after_number = !character && self.mode == OutputMode::Ascii;
+
+    text
+  }
+}
+
+pub fn format_outputs(outputs: &[i64], mode: OutputMode) -> String {
+  let mut formatter = OutputFormatter::new(mode);
+  outputs.iter().map(|value| formatter.format(*value)).collect()
+}
+
+pub fn describe(outcome: &RunOutcome) -> String {
+  match outcome {
+    RunOutcome::Halted => "halted".to_string(),
+    RunOutcome::FellOffTape { ip } => format!("ran off the end of the program at address {}", ip),
+    RunOutcome::InputExhausted => "needed more input than it was given".to_string(),
+    RunOutcome::LimitReached => "reached the instruction limit".to_string(),
+    RunOutcome::Faulted(e) => format!("faulted: {}", e)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(|s| s.to_string()).collect()
+  }
+
+  fn options(line: &str) -> Options {
+    parse_args(&args(line)).unwrap().unwrap()
+  }
+
+  #[test]
+  fn test_parse_args() {
+    assert_eq!(options("--input 1,2,-3 --ascii prog.txt --trace --profile --limit 100"), Options {
+      program: PathBuf::from("prog.txt"),
+      inputs: Inputs::Listed(vec![1, 2, -3]),
+      mode: OutputMode::Ascii,
+      trace: true,
+      profile: true,
+      limit: Some(100)
+    });
+
+    let defaults = options("prog.txt");
+    assert_eq!((defaults.inputs, defaults.mode, defaults.limit), (Inputs::Stdin, OutputMode::Numbers, None));
+    assert_eq!(parse_args(&args("--help")), Ok(None));
+  }
+
+  #[test]
+  fn test_parse_args_errors() {
+    assert!(parse_args(&args("")).is_err());
+    assert!(parse_args(&args("a.txt b.txt")).is_err());
+    assert!(parse_args(&args("a.txt --input 1,x")).is_err());
+    assert!(parse_args(&args("a.txt --limit")).is_err());
+    assert!(parse_args(&args("a.txt --verbose")).is_err());
+  }
+
+  #[test]
+  fn test_input_values() {
+    assert_eq!(parse_values("1, 2\n-3 4,"), Ok(vec![1, 2, -3, 4]));
+    assert_eq!(parse_values(""), Ok(vec![]));
+    assert_eq!(ascii_values("Hi\n"), vec![72, 105, 10]);
+  }
+
+  fn run(program: &[i64], line: &str) -> Execution {
+    execute(program.to_vec(), &options(line), std::io::empty(), |_| {})
+  }
+
+  #[test]
+  fn test_execute() {
+    // Adds its two inputs.
+    let program = parse_values("3,12,3,13,1,12,13,14,4,14,99").unwrap();
+
+    let execution = run(&program, "p --input 2,40 --profile");
+    assert_eq!(execution.outputs, vec![42]);
+    assert_eq!(execution.outcome, RunOutcome::Halted);
+    assert_eq!(execution.instructions, 5);
+
+    let profile = execution.profile.unwrap();
+    assert_eq!(profile.total(), 5);
+    assert_eq!(profile.opcodes.get(&3), Some(&2));
+    assert_eq!(profile.hottest(2), vec![(0, 1), (2, 1)]);
+
+    let execution = run(&program, "p --input 2");
+    assert_eq!(execution.outcome, RunOutcome::InputExhausted);
+    assert!(execution.profile.is_none());
+
+    assert_eq!(run(&program, "p --input 2,40 --limit 3").outcome, RunOutcome::LimitReached);
+  }
+
+  #[test]
+  fn test_execute_streams() {
+    // Echoes every input until it reads a 0.
+    let program = parse_values("3,100,4,100,1005,100,0,99").unwrap();
+
+    let mut seen = Vec::new();
+    let execution = execute(program.clone(), &options("p"), std::io::Cursor::new("1, 2\n3\n0\n"), |value| seen.push(value));
+    assert_eq!(seen, vec![1, 2, 3, 0]);
+    assert_eq!(execution.outcome, RunOutcome::Halted);
+
+    let execution = execute(program.clone(), &options("p --ascii"), std::io::Cursor::new("Hi\n"), |_| {});
+    assert_eq!(execution.outputs, vec![72, 105, 10]);
+    assert_eq!(execution.outcome, RunOutcome::InputExhausted);
+
+    // The output port closes even though the machine could be resumed.
+    let execution = execute(parse_values("104,7,1105,1,0").unwrap(), &options("p --input 0 --limit 5"), std::io::empty(), |_| {});
+    assert_eq!((execution.outputs.len(), execution.outcome), (3, RunOutcome::LimitReached));
+  }
+
+  #[test]
+  fn test_format_outputs() {
+    assert_eq!(format_outputs(&[1, -2], OutputMode::Numbers), "1\n-2\n");
+    assert_eq!(format_outputs(&[72, 105, 12345, 10, 79, 75], OutputMode::Ascii), "Hi\n12345\nOK");
+    assert_eq!(format_outputs(&[12345, 10, 10, 79], OutputMode::Ascii), "12345\n\nO");
+
+    let mut formatter = OutputFormatter::new(OutputMode::Ascii);
+    assert_eq!([79, 75, 12345, 10].iter().map(|v| formatter.format(*v)).collect::<Vec<_>>(), vec!["O", "K", "\n12345\n", ""]);
+  }
+}
@@ -0,0 +1,170 @@
+use std::marker::PhantomData;
+use bus::BusReader;
+
+// A record made of a fixed number of consecutive output values, such as (x, y, tile).
+pub trait Frame: Sized {
+  const WIDTH: usize;
+
+  fn decode(values: &[i64]) -> Self;
+}
+
+impl Frame for (i64, i64) {
+  const WIDTH: usize = 2;
+
+  fn decode(values: &[i64]) -> Self {
+    (values[0], values[1])
+  }
+}
+
+impl Frame for (i64, i64, i64) {
+  const WIDTH: usize = 3;
+
+  fn decode(values: &[i64]) -> Self {
+    (values[0], values[1], values[2])
+  }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct IncompleteFrame {
+  pub values: Vec<i64>,
+  pub expected: usize
+}
+
+impl std::fmt::Display for IncompleteFrame {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Output ended {} values into a {} value frame: {:?}", self.values.len(), self.expected, self.values)
+  }
+}
+
+impl std::error::Error for IncompleteFrame {}
+
+// Yields a frame for every WIDTH outputs. Once the machine stops for good its output bus closes,
+// any leftover values are reported as an IncompleteFrame and the iterator ends.
+pub struct FrameReader<F: Frame> {
+  port: BusReader<i64>,
+  buffer: Vec<i64>,
+  finished: bool,
+  frame: PhantomData<F>
+}
+
+impl<F: Frame> FrameReader<F> {
+  pub fn new(port: BusReader<i64>) -> FrameReader<F> {
+    FrameReader {
+      port,
+      buffer: Vec::with_capacity(F::WIDTH),
+      finished: false,
+      frame: PhantomData
+    }
+  }
+}
+
+impl<F: Frame> Iterator for FrameReader<F> {
+  type Item = Result<F, IncompleteFrame>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.finished {
+      return None;
+    }
+
+    while self.buffer.len() < F::WIDTH {
+      match self.port.recv() {
+        Ok(value) => self.buffer.push(value),
+        Err(_) => {
+          self.finished = true;
+
+          if self.buffer.is_empty() {
+            return None;
+          }
+
+          return Some(Err(IncompleteFrame { values: self.buffer.split_off(0), expected: F::WIDTH }));
+        }
+      }
+    }
+
+    let frame = F::decode(&self.buffer);
+    self.buffer.clear();
+    Some(Ok(frame))
+  }
+}
+
+pub fn decode_frames<F: Frame>(values: &[i64]) -> (Vec<F>, Option<IncompleteFrame>) {
+  let chunks = values.chunks_exact(F::WIDTH);
+  let remainder = chunks.remainder();
+
+  let incomplete = if remainder.is_empty() {
+    None
+  } else {
+    Some(IncompleteFrame { values: remainder.to_vec(), expected: F::WIDTH })
+  };
+
+  (chunks.map(F::decode).collect(), incomplete)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::inputhandling::Input;
+  use super::super::intcode_8086::{ Intcode8086, RunOutcome };
+
+  #[derive(PartialEq, Eq, Debug)]
+  struct Packet {
+    destination: i64,
+    x: i64,
+    y: i64
+  }
+
+  impl Frame for Packet {
+    const WIDTH: usize = 3;
+
+    fn decode(values: &[i64]) -> Self {
+      Packet { destination: values[0], x: values[1], y: values[2] }
+    }
+  }
+
+  #[test]
+  fn test_frames_from_running_machine() {
+    let mut cpu = Intcode8086::initialize(Input::inline("104,1,104,2,104,3,104,4,104,5,104,6,99").csv().unwrap());
+    let frames : FrameReader<Packet> = cpu.get_frame_port();
+
+    cpu.process().join().expect("");
+
+    assert_eq!(frames.collect::<Vec<_>>(), vec![
+      Ok(Packet { destination: 1, x: 2, y: 3 }),
+      Ok(Packet { destination: 4, x: 5, y: 6 })
+    ]);
+  }
+
+  #[test]
+  fn test_incomplete_frame_at_halt() {
+    let mut cpu = Intcode8086::initialize(Input::inline("104,1,104,2,104,3,104,4,99").csv().unwrap());
+    let frames : FrameReader<(i64, i64, i64)> = cpu.get_frame_port();
+
+    cpu.process().join().expect("");
+
+    assert_eq!(frames.collect::<Vec<_>>(), vec![
+      Ok((1, 2, 3)),
+      Err(IncompleteFrame { values: vec![4], expected: 3 })
+    ]);
+  }
+
+  #[test]
+  fn test_frames_end_while_machine_is_kept() {
+    let mut cpu = Intcode8086::initialize(Input::inline("104,1,104,2,104,3,104,4,99").csv().unwrap());
+    let frames : FrameReader<(i64, i64)> = cpu.get_frame_port();
+
+    let (cpu, outcome) = cpu.process().join().expect("");
+
+    assert_eq!(outcome, RunOutcome::Halted);
+    assert_eq!(frames.collect::<Vec<_>>(), vec![Ok((1, 2)), Ok((3, 4))]);
+    assert_eq!(cpu.get_memory_at(8), 99);
+  }
+
+  #[test]
+  fn test_decode_frames() {
+    let (frames, incomplete) = decode_frames::<(i64, i64)>(&[1, 2, 3, 4, 5]);
+
+    assert_eq!(frames, vec![(1, 2), (3, 4)]);
+    assert_eq!(incomplete, Some(IncompleteFrame { values: vec![5], expected: 2 }));
+    assert_eq!(decode_frames::<(i64, i64)>(&[1, 2]).1, None);
+  }
+}
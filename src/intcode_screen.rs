@@ -0,0 +1,296 @@
+use std::collections::{ HashMap, HashSet };
+use std::convert::TryFrom;
+use std::io::Write;
+use std::path::Path;
+use bmp::{ Image, Pixel };
+use super::intcode_frames::Frame;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct ScreenWrite {
+  pub x: i64,
+  pub y: i64,
+  pub value: i64
+}
+
+impl Frame for ScreenWrite {
+  const WIDTH: usize = 3;
+
+  fn decode(values: &[i64]) -> Self {
+    ScreenWrite { x: values[0], y: values[1], value: values[2] }
+  }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Glyph {
+  pub symbol: char,
+  pub color: Pixel
+}
+
+pub struct Palette {
+  tiles: HashMap<i64, Glyph>,
+  background: Glyph,
+  unknown: Glyph
+}
+
+impl Default for Palette {
+  fn default() -> Self {
+    Palette::new()
+  }
+}
+
+impl Palette {
+  pub fn new() -> Palette {
+    Palette {
+      tiles: HashMap::new(),
+      background: Glyph { symbol: ' ', color: Pixel::new(0, 0, 0) },
+      unknown: Glyph { symbol: '?', color: Pixel::new(255, 0, 255) }
+    }
+  }
+
+  pub fn set(&mut self, tile: i64, symbol: char, color: Pixel) {
+    self.tiles.insert(tile, Glyph { symbol, color });
+  }
+
+  // Used for cells inside the screen's bounds that were never drawn.
+  pub fn set_background(&mut self, symbol: char, color: Pixel) {
+    self.background = Glyph { symbol, color };
+  }
+
+  pub fn glyph(&self, tile: Option<i64>) -> Glyph {
+    match tile {
+      Some(tile) => self.tiles.get(&tile).copied().unwrap_or(self.unknown),
+      None => self.background
+    }
+  }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Bounds {
+  pub min_x: i64,
+  pub min_y: i64,
+  pub max_x: i64,
+  pub max_y: i64
+}
+
+// Tiles drawn so far apart that the screen between them can't be rendered.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct ScreenTooLarge {
+  pub bounds: Bounds
+}
+
+impl std::fmt::Display for ScreenTooLarge {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let b = self.bounds;
+    write!(f, "A screen from ({}, {}) to ({}, {}) is too large to render", b.min_x, b.min_y, b.max_x, b.max_y)
+  }
+}
+
+impl std::error::Error for ScreenTooLarge {}
+
+fn span(min: i64, max: i64) -> Option<u32> {
+  u32::try_from(max.checked_sub(min)?.checked_add(1)?).ok()
+}
+
+impl Bounds {
+  pub fn width(&self) -> Result<u32, ScreenTooLarge> {
+    span(self.min_x, self.max_x).ok_or(ScreenTooLarge { bounds: *self })
+  }
+
+  pub fn height(&self) -> Result<u32, ScreenTooLarge> {
+    span(self.min_y, self.max_y).ok_or(ScreenTooLarge { bounds: *self })
+  }
+}
+
+// A sparse grid of tiles. Writes to a registered sentinel coordinate, such as a score at
+// (-1, 0), update that register instead of drawing a tile.
+#[derive(Default)]
+pub struct Screen {
+  tiles: HashMap<(i64, i64), i64>,
+  sentinels: HashSet<(i64, i64)>,
+  registers: HashMap<(i64, i64), i64>
+}
+
+impl Screen {
+  pub fn new() -> Screen {
+    Screen::default()
+  }
+
+  pub fn add_register(&mut self, x: i64, y: i64) {
+    self.sentinels.insert((x, y));
+  }
+
+  pub fn apply(&mut self, write: ScreenWrite) {
+    if self.sentinels.contains(&(write.x, write.y)) {
+      self.registers.insert((write.x, write.y), write.value);
+    } else {
+      self.tiles.insert((write.x, write.y), write.value);
+    }
+  }
+
+  pub fn tile_at(&self, x: i64, y: i64) -> Option<i64> {
+    self.tiles.get(&(x, y)).copied()
+  }
+
+  pub fn register(&self, x: i64, y: i64) -> Option<i64> {
+    self.registers.get(&(x, y)).copied()
+  }
+
+  pub fn count(&self, tile: i64) -> usize {
+    self.tiles.values().filter(|t| **t == tile).count()
+  }
+
+  pub fn bounds(&self) -> Option<Bounds> {
+    if self.tiles.is_empty() {
+      return None;
+    }
+
+    Some(Bounds {
+      min_x: self.tiles.keys().map(|(x, _)| *x).min()?,
+      min_y: self.tiles.keys().map(|(_, y)| *y).min()?,
+      max_x: self.tiles.keys().map(|(x, _)| *x).max()?,
+      max_y: self.tiles.keys().map(|(_, y)| *y).max()?
+    })
+  }
+
+  pub fn render(&self, palette: &Palette) -> Result<String, ScreenTooLarge> {
+    let mut out = String::new();
+
+    if let Some(bounds) = self.bounds() {
+      let (width, height) = (bounds.width()?, bounds.height()?);
+      out.reserve((width as usize + 1) * height as usize);
+
+      for y in bounds.min_y..=bounds.max_y {
+        for x in bounds.min_x..=bounds.max_x {
+          out.push(palette.glyph(self.tile_at(x, y)).symbol);
+        }
+
+        out.push('\n');
+      }
+    }
+
+    Ok(out)
+  }
+
+  // Draws over the previous frame in a terminal: the cursor goes back to the top left and
+  // whatever was left below the new frame is cleared.
+  pub fn render_to<W: Write>(&self, palette: &Palette, out: &mut W) -> std::io::Result<()> {
+    let frame = self.render(palette).map_err(std::io::Error::other)?;
+
+    write!(out, "\x1b[H{}\x1b[J", frame)?;
+    out.flush()
+  }
+
+  pub fn to_image(&self, palette: &Palette, scale: u32) -> Result<Image, ScreenTooLarge> {
+    let bounds = match self.bounds() {
+      Some(bounds) => bounds,
+      None => return Ok(Image::new(0, 0))
+    };
+
+    let too_large = ScreenTooLarge { bounds };
+    let width = bounds.width()?.checked_mul(scale).ok_or(too_large)?;
+    let height = bounds.height()?.checked_mul(scale).ok_or(too_large)?;
+    let mut image = Image::new(width, height);
+
+    for (x, y) in image.coordinates() {
+      let tile = self.tile_at(bounds.min_x + (x / scale) as i64, bounds.min_y + (y / scale) as i64);
+      image.set_pixel(x, y, palette.glyph(tile).color);
+    }
+
+    Ok(image)
+  }
+
+  pub fn save_bmp<P: AsRef<Path>>(&self, palette: &Palette, scale: u32, path: P) -> std::io::Result<()> {
+    self.to_image(palette, scale).map_err(std::io::Error::other)?.save(path)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::inputhandling::Input;
+  use super::super::intcode_8086::Intcode8086;
+  use super::super::intcode_frames::FrameReader;
+
+  fn palette() -> Palette {
+    let mut palette = Palette::new();
+    palette.set(1, '#', Pixel::new(255, 255, 255));
+    palette.set(2, 'o', Pixel::new(255, 0, 0));
+    palette
+  }
+
+  #[test]
+  fn test_screen_from_machine() {
+    let mut cpu = Intcode8086::initialize(Input::inline("104,1,104,2,104,3,104,6,104,5,104,4,104,-1,104,0,104,12345,99").csv().unwrap());
+    let frames : FrameReader<ScreenWrite> = cpu.get_frame_port();
+    cpu.process().join().expect("");
+
+    let mut screen = Screen::new();
+    screen.add_register(-1, 0);
+    for write in frames {
+      screen.apply(write.unwrap());
+    }
+
+    assert_eq!(screen.tile_at(1, 2), Some(3));
+    assert_eq!(screen.tile_at(6, 5), Some(4));
+    assert_eq!(screen.tile_at(-1, 0), None);
+    assert_eq!(screen.register(-1, 0), Some(12345));
+    assert_eq!(screen.bounds(), Some(Bounds { min_x: 1, min_y: 2, max_x: 6, max_y: 5 }));
+  }
+
+  #[test]
+  fn test_render_text() {
+    let mut screen = Screen::new();
+    screen.apply(ScreenWrite { x: 0, y: 0, value: 1 });
+    screen.apply(ScreenWrite { x: 2, y: 0, value: 1 });
+    screen.apply(ScreenWrite { x: 1, y: 1, value: 2 });
+    screen.apply(ScreenWrite { x: 2, y: 1, value: 7 });
+
+    assert_eq!(screen.render(&palette()).unwrap(), "# #\n o?\n");
+    assert_eq!(screen.count(1), 2);
+  }
+
+  #[test]
+  fn test_render_to_terminal() {
+    let mut screen = Screen::new();
+    screen.apply(ScreenWrite { x: 0, y: 0, value: 1 });
+    screen.apply(ScreenWrite { x: 1, y: 0, value: 2 });
+
+    let mut terminal = Vec::new();
+    screen.render_to(&palette(), &mut terminal).unwrap();
+    screen.apply(ScreenWrite { x: 1, y: 0, value: 1 });
+    screen.render_to(&palette(), &mut terminal).unwrap();
+
+    assert_eq!(String::from_utf8(terminal).unwrap(), "\x1b[H#o\n\x1b[J\x1b[H##\n\x1b[J");
+  }
+
+  #[test]
+  fn test_far_apart_tiles_are_too_large() {
+    let mut screen = Screen::new();
+    screen.apply(ScreenWrite { x: i64::MIN, y: 0, value: 1 });
+    screen.apply(ScreenWrite { x: i64::MAX, y: 0, value: 1 });
+
+    let bounds = screen.bounds().unwrap();
+    assert_eq!(bounds.width(), Err(ScreenTooLarge { bounds }));
+    assert_eq!(bounds.height(), Ok(1));
+    assert!(screen.render(&palette()).is_err());
+    assert!(screen.render_to(&palette(), &mut Vec::new()).is_err());
+
+    let mut screen = Screen::new();
+    screen.apply(ScreenWrite { x: 0, y: 0, value: 1 });
+    screen.apply(ScreenWrite { x: 70000, y: 0, value: 1 });
+    assert!(screen.to_image(&palette(), 70000).is_err());
+  }
+
+  #[test]
+  fn test_render_image() {
+    let mut screen = Screen::new();
+    screen.apply(ScreenWrite { x: 0, y: 0, value: 1 });
+    screen.apply(ScreenWrite { x: 1, y: 0, value: 2 });
+
+    let image = screen.to_image(&palette(), 2).unwrap();
+
+    assert_eq!((image.get_width(), image.get_height()), (4, 2));
+    assert_eq!(image.get_pixel(1, 1), Pixel::new(255, 255, 255));
+    assert_eq!(image.get_pixel(2, 0), Pixel::new(255, 0, 0));
+  }
+}
@@ -0,0 +1,281 @@
+use std::sync::Arc;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::thread;
+use crossbeam_channel::unbounded;
+use itertools::Itertools;
+use super::intcode_8086::{ Intcode8086, RunOutcome };
+
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Variant {
+  pub patches: Vec<(usize, i64)>,
+  pub inputs: Vec<i64>
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct VariantOutcome {
+  pub variant: Variant,
+  pub memory: Vec<(usize, i64)>,
+  pub outputs: Vec<i64>,
+  pub outcome: RunOutcome
+}
+
+impl VariantOutcome {
+  pub fn memory_at(&self, address: usize) -> Option<i64> {
+    self.memory.iter().find(|(a, _)| *a == address).map(|(_, v)| *v)
+  }
+}
+
+pub struct VariantSearch {
+  von_neumann_tape: Arc<Vec<i64>>,
+  memory_axes: Vec<(usize, Vec<i64>)>,
+  input_sets: Vec<Vec<i64>>,
+  watched: Vec<usize>,
+  workers: usize,
+  instruction_limit: u64
+}
+
+// Generous for the puzzles' programs, but keeps a variant that loops forever from holding up
+// the whole search.
+pub const DEFAULT_INSTRUCTION_LIMIT: u64 = 1_000_000;
+
+impl VariantSearch {
+  pub fn new(von_neumann_tape: Vec<i64>) -> VariantSearch {
+    VariantSearch {
+      von_neumann_tape: Arc::new(von_neumann_tape),
+      memory_axes: Vec::new(),
+      input_sets: Vec::new(),
+      watched: Vec::new(),
+      workers: 4,
+      instruction_limit: DEFAULT_INSTRUCTION_LIMIT
+    }
+  }
+
+  pub fn vary_memory<I: IntoIterator<Item = i64>>(&mut self, address: usize, values: I) {
+    self.memory_axes.push((address, values.into_iter().collect()));
+  }
+
+  pub fn add_inputs(&mut self, inputs: Vec<i64>) {
+    self.input_sets.push(inputs);
+  }
+
+  pub fn watch(&mut self, address: usize) {
+    self.watched.push(address);
+  }
+
+  pub fn set_workers(&mut self, workers: usize) {
+    self.workers = workers.max(1);
+  }
+
+  pub fn set_instruction_limit(&mut self, limit: u64) {
+    self.instruction_limit = limit;
+  }
+
+  // Every combination of the memory axes, crossed with every input set.
+  pub fn variants(&self) -> Vec<Variant> {
+    let patch_sets : Vec<Vec<(usize, i64)>> = if self.memory_axes.is_empty() {
+      vec![Vec::new()]
+    } else {
+      self.memory_axes
+        .iter()
+        .map(|(address, values)| values.iter().map(|v| (*address, *v)).collect::<Vec<(usize, i64)>>())
+        .multi_cartesian_product()
+        .collect()
+    };
+
+    let input_sets = if self.input_sets.is_empty() { vec![Vec::new()] } else { self.input_sets.clone() };
+
+    patch_sets
+      .iter()
+      .cartesian_product(input_sets.iter())
+      .map(|(patches, inputs)| Variant { patches: patches.clone(), inputs: inputs.clone() })
+      .collect()
+  }
+
+  pub fn run(&self) -> Vec<VariantOutcome> {
+    let mut table = Vec::new();
+    self.execute(|index, outcome| { table.push((index, outcome)); false });
+    table.sort_by_key(|(index, _)| *index);
+    table.into_iter().map(|(_, outcome)| outcome).collect()
+  }
+
+  // The first matching variant in `variants()` order, whichever worker gets there first.
+  // Variants after a match are no longer handed out, but earlier ones still run to completion.
+  pub fn run_until<P: FnMut(&VariantOutcome) -> bool>(&self, mut predicate: P) -> Option<VariantOutcome> {
+    let mut found : Option<(usize, VariantOutcome)> = None;
+
+    self.execute(|index, outcome| {
+      if found.as_ref().is_some_and(|(best, _)| index > *best) || !predicate(&outcome) {
+        return false;
+      }
+
+      found = Some((index, outcome));
+      true
+    });
+
+    found.map(|(_, outcome)| outcome)
+  }
+
+  // Once `on_outcome` returns true for a variant, no variant after it is started.
+  fn execute<F: FnMut(usize, VariantOutcome) -> bool>(&self, mut on_outcome: F) {
+    let (job_sender, job_receiver) = unbounded();
+    let (result_sender, result_receiver) = unbounded();
+    let cutoff = Arc::new(AtomicUsize::new(usize::MAX));
+
+    for job in self.variants().into_iter().enumerate() {
+      job_sender.send(job).expect("The receiver is held locally");
+    }
+    drop(job_sender);
+
+    let handles : Vec<thread::JoinHandle<()>> = (0..self.workers).map(|_| {
+      let jobs = job_receiver.clone();
+      let results = result_sender.clone();
+      let cutoff = cutoff.clone();
+      let tape = self.von_neumann_tape.clone();
+      let watched = self.watched.clone();
+      let limit = self.instruction_limit;
+
+      thread::spawn(move || {
+        // Jobs come off the queue in index order, so everything left is past the cutoff too.
+        for (index, variant) in jobs.iter() {
+          if index > cutoff.load(Ordering::SeqCst) {
+            break;
+          }
+
+          let outcome = run_variant(&tape, &watched, limit, variant);
+
+          if results.send((index, outcome)).is_err() {
+            break;
+          }
+        }
+      })
+    }).collect();
+    drop(result_sender);
+
+    for (index, outcome) in result_receiver.iter() {
+      if on_outcome(index, outcome) {
+        cutoff.fetch_min(index, Ordering::SeqCst);
+      }
+    }
+
+    for handle in handles {
+      handle.join().expect("Search workers should not panic");
+    }
+  }
+}
+
+// Every variant starts from the shared tape; it's only copied once the variant patches it or
+// the program writes to memory, since from then on the variant's memory is its own.
+fn run_variant(von_neumann_tape: &Arc<Vec<i64>>, watched: &[usize], limit: u64, variant: Variant) -> VariantOutcome {
+  let mut cpu = Intcode8086::from_shared(von_neumann_tape.clone());
+  cpu.start_recording();
+  cpu.set_instruction_limit(limit);
+
+  for (address, value) in &variant.patches {
+    cpu.set_memory_at(*address, *value);
+  }
+
+  let port = cpu.get_input_port();
+  for input in &variant.inputs {
+    port.send(*input).expect("The machine holds the receiver");
+  }
+  drop(port);
+
+  let outcome = cpu.run();
+  let outputs = cpu.take_transcript().map(|t| t.outputs()).unwrap_or_default();
+  let memory = watched.iter().map(|a| (*a, cpu.get_memory_at(*a))).collect();
+
+  VariantOutcome { variant, memory, outputs, outcome }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::inputhandling::Input;
+
+  #[test]
+  fn test_variants_cover_every_combination() {
+    let mut search = VariantSearch::new(Input::inline("1,0,0,0,99").csv().unwrap());
+    search.vary_memory(1, 0..3);
+    search.vary_memory(2, 0..2);
+    search.add_inputs(vec![1]);
+    search.add_inputs(vec![2]);
+
+    let variants = search.variants();
+
+    assert_eq!(variants.len(), 12);
+    assert_eq!(variants[0], Variant { patches: vec![(1, 0), (2, 0)], inputs: vec![1] });
+    assert_eq!(variants[11], Variant { patches: vec![(1, 2), (2, 1)], inputs: vec![2] });
+  }
+
+  #[test]
+  fn test_run_table() {
+    let mut search = VariantSearch::new(Input::inline("1,0,0,0,99").csv().unwrap());
+    search.vary_memory(1, 0..5);
+    search.vary_memory(2, 0..5);
+    search.watch(0);
+    search.watch(10);
+
+    let table = search.run();
+
+    assert_eq!(table.len(), 25);
+    assert!(table.iter().all(|row| row.outcome == RunOutcome::Halted));
+    assert_eq!(table[4].variant.patches, vec![(1, 0), (2, 4)]);
+    assert_eq!(table[4].memory_at(0), Some(100));
+    assert_eq!(table[4].memory_at(10), Some(0));
+  }
+
+  #[test]
+  fn test_run_collects_outputs_per_input_set() {
+    let mut search = VariantSearch::new(Input::inline("3,9,8,9,10,9,4,9,99,-1,8").csv().unwrap());
+    for input in 0..10 {
+      search.add_inputs(vec![input]);
+    }
+
+    let table = search.run();
+
+    assert_eq!(table.len(), 10);
+    assert_eq!(table[8].outputs, vec![1]);
+    assert!(table.iter().filter(|row| row.variant.inputs != vec![8]).all(|row| row.outputs == vec![0]));
+  }
+
+  #[test]
+  fn test_run_until() {
+    let mut search = VariantSearch::new(Input::inline("1,0,0,0,99").csv().unwrap());
+    search.vary_memory(1, 0..5);
+    search.vary_memory(2, 0..5);
+    search.watch(0);
+    search.set_workers(2);
+
+    let found = search.run_until(|row| row.memory_at(0) == Some(100)).unwrap();
+    assert_eq!(found.memory_at(0), Some(100));
+
+    // Several patches add up to 100; the earlier variant wins however the workers are scheduled.
+    search.set_workers(8);
+    for _ in 0..20 {
+      assert_eq!(search.run_until(|row| row.memory_at(0) == Some(100)).unwrap().variant.patches, vec![(1, 0), (2, 4)]);
+    }
+
+    assert_eq!(search.run_until(|row| row.memory_at(0) == Some(1000)), None);
+  }
+
+  #[test]
+  fn test_instruction_limit() {
+    let mut search = VariantSearch::new(Input::inline("1105,1,0").csv().unwrap());
+    search.set_instruction_limit(100);
+
+    assert_eq!(search.run()[0].outcome, RunOutcome::LimitReached);
+  }
+
+  #[test]
+  fn test_runaway_variant_stops_at_the_default_limit() {
+    // With memory[1] = 1 the first instruction jumps back to itself forever.
+    let mut search = VariantSearch::new(Input::inline("1105,0,0,104,7,99").csv().unwrap());
+    search.vary_memory(1, 0..2);
+
+    let table = search.run();
+    assert_eq!(table[0].outputs, vec![7]);
+    assert_eq!(table[1].outcome, RunOutcome::LimitReached);
+
+    assert_eq!(search.run_until(|row| row.outcome == RunOutcome::LimitReached).unwrap().variant.patches, vec![(1, 1)]);
+  }
+}
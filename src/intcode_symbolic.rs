@@ -0,0 +1,664 @@
+use std::collections::{ BTreeMap, BTreeSet };
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
+pub enum Symbol {
+  Input(usize),
+  Cell(usize)
+}
+
+impl std::fmt::Display for Symbol {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Symbol::Input(n) => write!(f, "input[{}]", n),
+      Symbol::Cell(addr) => write!(f, "mem[{}]", addr)
+    }
+  }
+}
+
+pub type Assignment = BTreeMap<Symbol, i64>;
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Expr {
+  Const(i64),
+  Unknown(Symbol),
+  Add(Rc<Expr>, Rc<Expr>),
+  Multiply(Rc<Expr>, Rc<Expr>),
+  LessThan(Rc<Expr>, Rc<Expr>),
+  Equals(Rc<Expr>, Rc<Expr>),
+  // A read through an address that depends on an unknown, resolved against the memory
+  // as it was when the read happened.
+  Load { address: Rc<Expr>, memory: Rc<Vec<Rc<Expr>>> }
+}
+
+impl Expr {
+  // None when two constants fold to something that doesn't fit in an i64.
+  fn add(a: Rc<Expr>, b: Rc<Expr>) -> Option<Rc<Expr>> {
+    Some(match (a.as_const(), b.as_const()) {
+      (Some(x), Some(y)) => Rc::new(Expr::Const(x.checked_add(y)?)),
+      (Some(0), _) => b,
+      (_, Some(0)) => a,
+      _ => Rc::new(Expr::Add(a, b))
+    })
+  }
+
+  fn multiply(a: Rc<Expr>, b: Rc<Expr>) -> Option<Rc<Expr>> {
+    Some(match (a.as_const(), b.as_const()) {
+      (Some(x), Some(y)) => Rc::new(Expr::Const(x.checked_mul(y)?)),
+      (Some(0), _) | (_, Some(0)) => Rc::new(Expr::Const(0)),
+      (Some(1), _) => b,
+      (_, Some(1)) => a,
+      _ => Rc::new(Expr::Multiply(a, b))
+    })
+  }
+
+  fn less_than(a: Rc<Expr>, b: Rc<Expr>) -> Rc<Expr> {
+    match (a.as_const(), b.as_const()) {
+      (Some(x), Some(y)) => Rc::new(Expr::Const(if x < y { 1 } else { 0 })),
+      _ => Rc::new(Expr::LessThan(a, b))
+    }
+  }
+
+  fn equals(a: Rc<Expr>, b: Rc<Expr>) -> Rc<Expr> {
+    match (a.as_const(), b.as_const()) {
+      (Some(x), Some(y)) => Rc::new(Expr::Const(if x == y { 1 } else { 0 })),
+      _ if a == b => Rc::new(Expr::Const(1)),
+      _ => Rc::new(Expr::Equals(a, b))
+    }
+  }
+
+  pub fn as_const(&self) -> Option<i64> {
+    match self {
+      Expr::Const(value) => Some(*value),
+      _ => None
+    }
+  }
+
+  pub fn eval(&self, assignment: &Assignment) -> Option<i64> {
+    match self {
+      Expr::Const(value) => Some(*value),
+      Expr::Unknown(symbol) => assignment.get(symbol).copied(),
+      Expr::Add(a, b) => a.eval(assignment)?.checked_add(b.eval(assignment)?),
+      Expr::Multiply(a, b) => a.eval(assignment)?.checked_mul(b.eval(assignment)?),
+      Expr::LessThan(a, b) => Some(if a.eval(assignment)? < b.eval(assignment)? { 1 } else { 0 }),
+      Expr::Equals(a, b) => Some(if a.eval(assignment)? == b.eval(assignment)? { 1 } else { 0 }),
+      Expr::Load { address, memory } => {
+        let address = address.eval(assignment)?;
+
+        if address < 0 {
+          None
+        } else {
+          match memory.get(address as usize) {
+            Some(cell) => cell.eval(assignment),
+            None => Some(0)
+          }
+        }
+      }
+    }
+  }
+
+  pub fn symbols(&self, found: &mut BTreeSet<Symbol>) {
+    match self {
+      Expr::Const(_) => {},
+      Expr::Unknown(symbol) => { found.insert(*symbol); },
+      Expr::Add(a, b) | Expr::Multiply(a, b) | Expr::LessThan(a, b) | Expr::Equals(a, b) => {
+        a.symbols(found);
+        b.symbols(found);
+      },
+      Expr::Load { address, memory } => {
+        address.symbols(found);
+        for cell in memory.iter() {
+          cell.symbols(found);
+        }
+      }
+    }
+  }
+}
+
+impl std::fmt::Display for Expr {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Expr::Const(value) => write!(f, "{}", value),
+      Expr::Unknown(symbol) => write!(f, "{}", symbol),
+      Expr::Add(a, b) => write!(f, "({} + {})", a, b),
+      Expr::Multiply(a, b) => write!(f, "({} * {})", a, b),
+      Expr::LessThan(a, b) => write!(f, "({} < {})", a, b),
+      Expr::Equals(a, b) => write!(f, "({} == {})", a, b),
+      Expr::Load { address, .. } => write!(f, "mem[{}]", address)
+    }
+  }
+}
+
+// A branch taken on a path: `condition` evaluated non-zero exactly when `holds` is true.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Constraint {
+  pub condition: Rc<Expr>,
+  pub holds: bool
+}
+
+impl Constraint {
+  fn is_satisfied(&self, assignment: &Assignment) -> bool {
+    match self.condition.eval(assignment) {
+      Some(value) => (value != 0) == self.holds,
+      None => false
+    }
+  }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum PathEnd {
+  Halted,
+  FellOffTape { ip: usize },
+  InputExhausted,
+  StepLimitReached,
+  // Forking here would take the exploration past its path limit.
+  PathLimitReached,
+  UnknownOpcode { ip: usize },
+  SymbolicOpcode { ip: usize },
+  SymbolicAddress { ip: usize },
+  SymbolicJump { ip: usize },
+  NegativeAddress { ip: usize, address: i64 },
+  Overflow { ip: usize },
+  // A parameter mode digit other than 0, 1 or 2, which Intcode8086 faults on too.
+  InvalidMode { ip: usize }
+}
+
+#[derive(Clone, Debug)]
+pub struct SymbolicPath {
+  pub memory: Vec<Rc<Expr>>,
+  pub outputs: Vec<Rc<Expr>>,
+  pub constraints: Vec<Constraint>,
+  pub end: PathEnd
+}
+
+impl SymbolicPath {
+  pub fn memory_at(&self, address: usize) -> Rc<Expr> {
+    self.memory.get(address).cloned().unwrap_or_else(|| Rc::new(Expr::Const(0)))
+  }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Target {
+  Memory(usize),
+  Output(usize)
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum SolveError {
+  UnboundedSymbol(Symbol)
+}
+
+impl std::fmt::Display for SolveError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      SolveError::UnboundedSymbol(symbol) => write!(f, "No domain was given for {}", symbol)
+    }
+  }
+}
+
+impl std::error::Error for SolveError {}
+
+pub struct SymbolicIntcode {
+  von_neumann_tape: Vec<Rc<Expr>>,
+  inputs: Vec<Rc<Expr>>,
+  max_steps: u64,
+  max_paths: usize
+}
+
+#[derive(Clone)]
+struct State {
+  instruction_pointer: usize,
+  relative_base_pointer: i64,
+  // Shared with forked paths and loads until this path writes to it.
+  memory: Rc<Vec<Rc<Expr>>>,
+  next_input: usize,
+  outputs: Vec<Rc<Expr>>,
+  constraints: Vec<Constraint>,
+  steps: u64
+}
+
+enum Step {
+  Continue,
+  Fork(Rc<Expr>, usize),
+  End(PathEnd)
+}
+
+impl SymbolicIntcode {
+  pub fn initialize(von_neumann_tape: &[i64]) -> SymbolicIntcode {
+    SymbolicIntcode {
+      von_neumann_tape: von_neumann_tape.iter().map(|v| Rc::new(Expr::Const(*v))).collect(),
+      inputs: Vec::new(),
+      max_steps: 100_000,
+      max_paths: 1_000
+    }
+  }
+
+  pub fn unknown_cell(&mut self, address: usize) {
+    if address >= self.von_neumann_tape.len() {
+      self.von_neumann_tape.resize(address + 1, Rc::new(Expr::Const(0)));
+    }
+
+    self.von_neumann_tape[address] = Rc::new(Expr::Unknown(Symbol::Cell(address)));
+  }
+
+  pub fn push_input(&mut self, value: i64) {
+    self.inputs.push(Rc::new(Expr::Const(value)));
+  }
+
+  pub fn push_unknown_input(&mut self) -> Symbol {
+    let symbol = Symbol::Input(self.inputs.len());
+    self.inputs.push(Rc::new(Expr::Unknown(symbol)));
+    symbol
+  }
+
+  pub fn set_max_steps(&mut self, max_steps: u64) {
+    self.max_steps = max_steps;
+  }
+
+  pub fn set_max_paths(&mut self, max_paths: usize) {
+    self.max_paths = max_paths;
+  }
+
+  pub fn explore(&self) -> Vec<SymbolicPath> {
+    let mut finished = Vec::new();
+    let mut pending = vec![State {
+      instruction_pointer: 0,
+      relative_base_pointer: 0,
+      memory: Rc::new(self.von_neumann_tape.clone()),
+      next_input: 0,
+      outputs: Vec::new(),
+      constraints: Vec::new(),
+      steps: 0
+    }];
+
+    while let Some(mut state) = pending.pop() {
+      let end = loop {
+        if state.steps >= self.max_steps {
+          break PathEnd::StepLimitReached;
+        }
+
+        match self.step(&mut state) {
+          Step::Continue => state.steps += 1,
+          Step::End(end) => break end,
+          Step::Fork(condition, target) => {
+            state.steps += 1;
+
+            if finished.len() + pending.len() + 1 >= self.max_paths {
+              break PathEnd::PathLimitReached;
+            }
+
+            let mut taken = state.clone();
+            taken.instruction_pointer = target;
+            taken.constraints.push(Constraint { condition: condition.clone(), holds: true });
+            pending.push(taken);
+
+            state.instruction_pointer += 3;
+            state.constraints.push(Constraint { condition, holds: false });
+          }
+        }
+      };
+
+      finished.push(SymbolicPath {
+        memory: Rc::try_unwrap(state.memory).unwrap_or_else(|shared| (*shared).clone()),
+        outputs: state.outputs,
+        constraints: state.constraints,
+        end
+      });
+    }
+
+    finished
+  }
+
+  fn step(&self, state: &mut State) -> Step {
+    let ip = state.instruction_pointer;
+
+    if ip >= state.memory.len() {
+      return Step::End(PathEnd::FellOffTape { ip });
+    }
+
+    let opcode = match state.memory[ip].as_const() {
+      Some(opcode) => opcode,
+      None => return Step::End(PathEnd::SymbolicOpcode { ip })
+    };
+
+    let parameters = match opcode % 100 {
+      1 | 2 | 7 | 8 => 3,
+      5 | 6 => 2,
+      3 | 4 | 9 => 1,
+      99 => 0,
+      _ => return Step::End(PathEnd::UnknownOpcode { ip })
+    };
+
+    if (1..=parameters).any(|position| (opcode / 10_i64.pow(position + 1)) % 10 > 2) {
+      return Step::End(PathEnd::InvalidMode { ip });
+    }
+
+    let mode = |position: u32| (opcode / 10_i64.pow(position + 1)) % 10;
+
+    let result = match opcode % 100 {
+      1 | 2 | 7 | 8 => {
+        let a = self.read(state, 1, mode(1));
+        let b = self.read(state, 2, mode(2));
+        let store = self.address(state, 3, mode(3));
+
+        match (a, b, store) {
+          (Ok(a), Ok(b), Ok(store)) => {
+            let value = match opcode % 100 {
+              1 => Expr::add(a, b),
+              2 => Expr::multiply(a, b),
+              7 => Some(Expr::less_than(a, b)),
+              _ => Some(Expr::equals(a, b))
+            };
+
+            value.map(|value| Some((store, value, 4))).ok_or(PathEnd::Overflow { ip })
+          },
+          (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => Err(e)
+        }
+      },
+      3 => match self.address(state, 1, mode(1)) {
+        Ok(store) => match self.inputs.get(state.next_input) {
+          Some(input) => {
+            state.next_input += 1;
+            Ok(Some((store, input.clone(), 2)))
+          },
+          None => Err(PathEnd::InputExhausted)
+        },
+        Err(e) => Err(e)
+      },
+      4 => match self.read(state, 1, mode(1)) {
+        Ok(value) => {
+          state.outputs.push(value);
+          Ok(None)
+        },
+        Err(e) => Err(e)
+      },
+      5 | 6 => {
+        let condition = self.read(state, 1, mode(1));
+        let target = self.read(state, 2, mode(2));
+
+        return match (condition, target) {
+          (Ok(condition), Ok(target)) => {
+            let target = match target.as_const() {
+              Some(target) if target < 0 => return Step::End(PathEnd::NegativeAddress { ip, address: target }),
+              Some(target) => target as usize,
+              None => return Step::End(PathEnd::SymbolicJump { ip })
+            };
+
+            // JumpIfFalse is JumpIfTrue on the negated condition.
+            let condition = if opcode % 100 == 6 { Expr::equals(condition, Rc::new(Expr::Const(0))) } else { condition };
+
+            match condition.as_const() {
+              Some(0) => { state.instruction_pointer += 3; Step::Continue },
+              Some(_) => { state.instruction_pointer = target; Step::Continue },
+              None => Step::Fork(condition, target)
+            }
+          },
+          (Err(e), _) | (_, Err(e)) => Step::End(e)
+        };
+      },
+      9 => match self.read(state, 1, mode(1)) {
+        Ok(value) => match value.as_const() {
+          Some(offset) => match state.relative_base_pointer.checked_add(offset) {
+            Some(base) => {
+              state.relative_base_pointer = base;
+              Ok(None)
+            },
+            None => Err(PathEnd::Overflow { ip })
+          },
+          None => Err(PathEnd::SymbolicAddress { ip })
+        },
+        Err(e) => Err(e)
+      },
+      99 => return Step::End(PathEnd::Halted),
+      _ => return Step::End(PathEnd::UnknownOpcode { ip })
+    };
+
+    match result {
+      Ok(Some((store, value, length))) => {
+        let memory = Rc::make_mut(&mut state.memory);
+        if store >= memory.len() {
+          memory.resize(store + 1, Rc::new(Expr::Const(0)));
+        }
+
+        memory[store] = value;
+        state.instruction_pointer += length;
+        Step::Continue
+      },
+      Ok(None) => {
+        state.instruction_pointer += 2;
+        Step::Continue
+      },
+      Err(end) => Step::End(end)
+    }
+  }
+
+  fn operand(state: &State, at_position: usize) -> Rc<Expr> {
+    match state.memory.get(state.instruction_pointer + at_position) {
+      Some(cell) => cell.clone(),
+      None => Rc::new(Expr::Const(0))
+    }
+  }
+
+  fn read(&self, state: &State, at_position: usize, mode: i64) -> Result<Rc<Expr>, PathEnd> {
+    let operand = SymbolicIntcode::operand(state, at_position);
+
+    let address = match mode {
+      1 => return Ok(operand),
+      2 => Expr::add(operand, Rc::new(Expr::Const(state.relative_base_pointer))).ok_or(PathEnd::Overflow { ip: state.instruction_pointer })?,
+      _ => operand
+    };
+
+    match address.as_const() {
+      Some(address) if address < 0 => Err(PathEnd::NegativeAddress { ip: state.instruction_pointer, address }),
+      Some(address) => Ok(state.memory.get(address as usize).cloned().unwrap_or_else(|| Rc::new(Expr::Const(0)))),
+      None => Ok(Rc::new(Expr::Load { address, memory: state.memory.clone() }))
+    }
+  }
+
+  fn address(&self, state: &State, at_position: usize, mode: i64) -> Result<usize, PathEnd> {
+    let operand = SymbolicIntcode::operand(state, at_position);
+
+    let address = match mode {
+      2 => Expr::add(operand, Rc::new(Expr::Const(state.relative_base_pointer))).ok_or(PathEnd::Overflow { ip: state.instruction_pointer })?,
+      _ => operand
+    };
+
+    match address.as_const() {
+      Some(address) if address < 0 => Err(PathEnd::NegativeAddress { ip: state.instruction_pointer, address }),
+      Some(address) => Ok(address as usize),
+      None => Err(PathEnd::SymbolicAddress { ip: state.instruction_pointer })
+    }
+  }
+}
+
+pub fn target_expr(path: &SymbolicPath, target: Target) -> Option<Rc<Expr>> {
+  match target {
+    Target::Memory(address) => Some(path.memory_at(address)),
+    Target::Output(index) => path.outputs.get(index).cloned()
+  }
+}
+
+// Finds an assignment of the unknowns, each drawn from its domain, under which some halted
+// path leaves `value` at `target`. Only the symbols a path actually depends on are enumerated.
+pub fn solve(paths: &[SymbolicPath], target: Target, value: i64, domains: &BTreeMap<Symbol, RangeInclusive<i64>>) -> Result<Option<Assignment>, SolveError> {
+  for path in paths.iter().filter(|p| p.end == PathEnd::Halted) {
+    let expr = match target_expr(path, target) {
+      Some(expr) => expr,
+      None => continue
+    };
+
+    let mut symbols = BTreeSet::new();
+    expr.symbols(&mut symbols);
+    for constraint in &path.constraints {
+      constraint.condition.symbols(&mut symbols);
+    }
+
+    let mut ranges = Vec::new();
+    for symbol in symbols {
+      match domains.get(&symbol) {
+        Some(range) => ranges.push((symbol, range.clone())),
+        None => return Err(SolveError::UnboundedSymbol(symbol))
+      }
+    }
+
+    let mut assignment = Assignment::new();
+    if search(&ranges, &mut assignment, &|a| expr.eval(a) == Some(value) && path.constraints.iter().all(|c| c.is_satisfied(a))) {
+      return Ok(Some(assignment));
+    }
+  }
+
+  Ok(None)
+}
+
+fn search(ranges: &[(Symbol, RangeInclusive<i64>)], assignment: &mut Assignment, accept: &dyn Fn(&Assignment) -> bool) -> bool {
+  match ranges.split_first() {
+    None => accept(assignment),
+    Some(((symbol, range), rest)) => {
+      for candidate in range.clone() {
+        assignment.insert(*symbol, candidate);
+
+        if search(rest, assignment, accept) {
+          return true;
+        }
+      }
+
+      assignment.remove(symbol);
+      false
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::inputhandling::Input;
+
+  #[test]
+  fn test_concrete_program() {
+    let paths = SymbolicIntcode::initialize(&Input::inline("1,1,1,4,99,5,6,0,99").csv().unwrap()).explore();
+
+    assert_eq!(paths.len(), 1);
+    assert_eq!(paths[0].end, PathEnd::Halted);
+    assert_eq!(paths[0].memory_at(0).as_const(), Some(30));
+  }
+
+  #[test]
+  fn test_expression_building() {
+    let mut cpu = SymbolicIntcode::initialize(&Input::inline("3,0,102,3,0,0,1001,0,4,0,99").csv().unwrap());
+    let input = cpu.push_unknown_input();
+    let paths = cpu.explore();
+
+    assert_eq!(paths.len(), 1);
+    assert_eq!(paths[0].memory_at(0).to_string(), "((3 * input[0]) + 4)");
+
+    let mut assignment = Assignment::new();
+    assignment.insert(input, 5);
+    assert_eq!(paths[0].memory_at(0).eval(&assignment), Some(19));
+  }
+
+  #[test]
+  fn test_branches_fork() {
+    let mut cpu = SymbolicIntcode::initialize(&Input::inline("3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9").csv().unwrap());
+    let input = cpu.push_unknown_input();
+    let paths = cpu.explore();
+
+    assert_eq!(paths.len(), 2);
+    assert!(paths.iter().all(|p| p.end == PathEnd::Halted && p.constraints.len() == 1));
+
+    let mut domains = BTreeMap::new();
+    domains.insert(input, -5..=5);
+
+    let zero = solve(&paths, Target::Output(0), 0, &domains).unwrap().unwrap();
+    assert_eq!(zero[&input], 0);
+
+    let one = solve(&paths, Target::Output(0), 1, &domains).unwrap().unwrap();
+    assert_ne!(one[&input], 0);
+  }
+
+  #[test]
+  fn test_symbolic_addresses_are_loaded() {
+    let mut cpu = SymbolicIntcode::initialize(&Input::inline("1,0,0,0,99").csv().unwrap());
+    cpu.unknown_cell(1);
+    cpu.unknown_cell(2);
+    let paths = cpu.explore();
+
+    let mut domains = BTreeMap::new();
+    domains.insert(Symbol::Cell(1), 0..=4);
+    domains.insert(Symbol::Cell(2), 0..=4);
+
+    let found = solve(&paths, Target::Memory(0), 100, &domains).unwrap().unwrap();
+    assert_eq!((found[&Symbol::Cell(1)], found[&Symbol::Cell(2)]), (0, 4));
+
+    assert_eq!(solve(&paths, Target::Memory(0), 1000, &domains), Ok(None));
+  }
+
+  #[test]
+  fn test_unbounded_symbol() {
+    let mut cpu = SymbolicIntcode::initialize(&Input::inline("3,0,99").csv().unwrap());
+    let input = cpu.push_unknown_input();
+    let paths = cpu.explore();
+
+    assert_eq!(solve(&paths, Target::Memory(0), 1, &BTreeMap::new()), Err(SolveError::UnboundedSymbol(input)));
+  }
+
+  #[test]
+  fn test_symbolic_store_address_stops_path() {
+    let mut cpu = SymbolicIntcode::initialize(&Input::inline("3,3,3,0,99").csv().unwrap());
+    cpu.push_unknown_input();
+    let paths = cpu.explore();
+
+    assert_eq!(paths[0].end, PathEnd::SymbolicAddress { ip: 2 });
+  }
+
+  #[test]
+  fn test_overflow_ends_path() {
+    let paths = SymbolicIntcode::initialize(&Input::inline("1102,4611686018427387904,4,0,99").csv().unwrap()).explore();
+    assert_eq!(paths[0].end, PathEnd::Overflow { ip: 0 });
+
+    let paths = SymbolicIntcode::initialize(&Input::inline("109,9223372036854775807,109,1,99").csv().unwrap()).explore();
+    assert_eq!(paths[0].end, PathEnd::Overflow { ip: 2 });
+  }
+
+  #[test]
+  fn test_path_limit() {
+    let mut cpu = SymbolicIntcode::initialize(&Input::inline("3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9").csv().unwrap());
+    cpu.push_unknown_input();
+    cpu.set_max_paths(1);
+    let paths = cpu.explore();
+
+    assert_eq!(paths.len(), 1);
+    assert_eq!(paths[0].end, PathEnd::PathLimitReached);
+
+    cpu.set_max_steps(1);
+    assert_eq!(cpu.explore()[0].end, PathEnd::StepLimitReached);
+  }
+
+  #[test]
+  fn test_invalid_mode_matches_concrete_machine() {
+    use super::super::intcode_8086::{ Intcode8086, IntcodeError, RunOutcome };
+
+    let program = Input::inline("1101,1,1,5,301,0,0,0,99").csv().unwrap();
+    let paths = SymbolicIntcode::initialize(&program).explore();
+    assert_eq!(paths[0].end, PathEnd::InvalidMode { ip: 4 });
+    assert_eq!(Intcode8086::initialize(program).run(), RunOutcome::Faulted(IntcodeError::UnknownParameterMode { ip: 4, opcode: 301 }));
+
+    // Digits beyond an instruction's own parameters aren't modes, so neither machine minds them.
+    let paths = SymbolicIntcode::initialize(&Input::inline("30104,0,99").csv().unwrap()).explore();
+    assert_eq!(paths[0].end, PathEnd::Halted);
+    assert_eq!(Intcode8086::initialize(Input::inline("30104,0,99").csv().unwrap()).run(), RunOutcome::Halted);
+  }
+
+  #[test]
+  fn test_loads_share_memory() {
+    let mut cpu = SymbolicIntcode::initialize(&Input::inline("4,0,4,0,99").csv().unwrap());
+    cpu.unknown_cell(1);
+    cpu.unknown_cell(3);
+    let paths = cpu.explore();
+
+    match (&*paths[0].outputs[0], &*paths[0].outputs[1]) {
+      (Expr::Load { memory: first, .. }, Expr::Load { memory: second, .. }) => assert!(Rc::ptr_eq(first, second)),
+      other => panic!("Expected two loads, got {:?}", other)
+    }
+  }
+}
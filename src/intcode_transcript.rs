@@ -0,0 +1,217 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+use super::intcode_8086::{ Intcode8086, RunOutcome };
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Event {
+  Input { step: u64, value: i64 },
+  Output { step: u64, value: i64 }
+}
+
+impl std::fmt::Display for Event {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Event::Input { step, value } => write!(f, "in {} {}", step, value),
+      Event::Output { step, value } => write!(f, "out {} {}", step, value)
+    }
+  }
+}
+
+impl Event {
+  fn parse(line: &str) -> Result<Event, Box<dyn Error>> {
+    let parts = line.split_whitespace().collect::<Vec<&str>>();
+
+    if parts.len() != 3 {
+      return Err(format!("Expected '<in|out> <step> <value>' but found '{}'", line).into());
+    }
+
+    let step : u64 = parts[1].parse()?;
+    let value : i64 = parts[2].parse()?;
+
+    match parts[0] {
+      "in" => Ok(Event::Input { step, value }),
+      "out" => Ok(Event::Output { step, value }),
+      other => Err(format!("Unknown event kind '{}'", other).into())
+    }
+  }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Transcript {
+  events: Vec<Event>
+}
+
+impl Transcript {
+  pub fn push(&mut self, event: Event) {
+    self.events.push(event);
+  }
+
+  pub fn events(&self) -> &[Event] {
+    &self.events
+  }
+
+  pub fn inputs(&self) -> Vec<i64> {
+    self.events.iter().filter_map(|e| match e { Event::Input { value, .. } => Some(*value), _ => None }).collect()
+  }
+
+  pub fn outputs(&self) -> Vec<i64> {
+    self.events.iter().filter_map(|e| match e { Event::Output { value, .. } => Some(*value), _ => None }).collect()
+  }
+
+  pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(self.to_string().as_bytes())
+  }
+
+  pub fn load<P: AsRef<Path>>(path: P) -> Result<Transcript, Box<dyn Error>> {
+    let mut file = File::open(path)?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    contents.parse()
+  }
+}
+
+impl std::fmt::Display for Transcript {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    for event in &self.events {
+      writeln!(f, "{}", event)?;
+    }
+
+    Ok(())
+  }
+}
+
+impl std::str::FromStr for Transcript {
+  type Err = Box<dyn Error>;
+
+  fn from_str(contents: &str) -> Result<Self, Self::Err> {
+    let events = contents
+      .lines()
+      .map(|s| s.trim())
+      .filter(|s| !s.is_empty())
+      .map(Event::parse)
+      .collect::<Result<Vec<Event>, Box<dyn Error>>>()?;
+
+    Ok(Transcript { events })
+  }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Divergence {
+  pub index: usize,
+  pub expected: Option<Event>,
+  pub actual: Option<Event>,
+  pub outcome: RunOutcome
+}
+
+impl std::fmt::Display for Divergence {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let describe = |e: &Option<Event>| match e {
+      Some(event) => event.to_string(),
+      None => "nothing".to_string()
+    };
+
+    write!(f, "Replay diverged at event {}: expected {} but got {} (run ended with {:?})",
+      self.index, describe(&self.expected), describe(&self.actual), self.outcome)
+  }
+}
+
+impl Error for Divergence {}
+
+pub fn record(mut cpu: Intcode8086, inputs: &[i64]) -> (Transcript, RunOutcome) {
+  cpu.start_recording();
+
+  let port = cpu.get_input_port();
+  for input in inputs {
+    port.send(*input).expect("The machine holds the receiver");
+  }
+  drop(port);
+
+  let outcome = cpu.run();
+  (cpu.take_transcript().unwrap_or_default(), outcome)
+}
+
+// The outputs of a program that ran to a halt. Any other stop comes back as the outcome along
+// with whatever was output before it.
+pub fn run_to_outputs(program: &[i64], inputs: &[i64]) -> Result<Vec<i64>, (RunOutcome, Vec<i64>)> {
+  let (transcript, outcome) = record(Intcode8086::initialize(program.to_vec()), inputs);
+
+  match outcome {
+    RunOutcome::Halted => Ok(transcript.outputs()),
+    outcome => Err((outcome, transcript.outputs()))
+  }
+}
+
+pub fn replay(von_neumann_tape: Vec<i64>, transcript: &Transcript) -> Result<RunOutcome, Divergence> {
+  let (actual, outcome) = record(Intcode8086::initialize(von_neumann_tape), &transcript.inputs());
+
+  let expected = transcript.events();
+  let actual = actual.events();
+
+  for index in 0..expected.len().max(actual.len()) {
+    let e = expected.get(index).copied();
+    let a = actual.get(index).copied();
+
+    if e != a {
+      return Err(Divergence { index, expected: e, actual: a, outcome });
+    }
+  }
+
+  Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::inputhandling::Input;
+
+  const LESS_THAN_8: &str = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99";
+
+  #[test]
+  fn test_recording() {
+    let (transcript, outcome) = record(Intcode8086::initialize(Input::inline("3,0,4,0,104,7,99").csv().unwrap()), &[365]);
+
+    assert_eq!(outcome, RunOutcome::Halted);
+    assert_eq!(transcript.events(), &[
+      Event::Input { step: 0, value: 365 },
+      Event::Output { step: 1, value: 365 },
+      Event::Output { step: 2, value: 7 }
+    ]);
+  }
+
+  #[test]
+  fn test_run_to_outputs() {
+    assert_eq!(run_to_outputs(&Input::inline("3,0,4,0,104,7,99").csv().unwrap(), &[365]), Ok(vec![365, 7]));
+    assert_eq!(run_to_outputs(&Input::inline("104,7,3,0,99").csv().unwrap(), &[]), Err((RunOutcome::InputExhausted, vec![7])));
+  }
+
+  #[test]
+  fn test_round_trip_text() {
+    let (transcript, _) = record(Intcode8086::initialize(Input::inline(LESS_THAN_8).csv().unwrap()), &[4]);
+    let parsed : Transcript = transcript.to_string().parse().unwrap();
+
+    assert_eq!(parsed, transcript);
+    assert_eq!(parsed.outputs(), vec![999]);
+  }
+
+  #[test]
+  fn test_replay_matches() {
+    let (transcript, _) = record(Intcode8086::initialize(Input::inline(LESS_THAN_8).csv().unwrap()), &[9]);
+
+    assert_eq!(replay(Input::inline(LESS_THAN_8).csv().unwrap(), &transcript), Ok(RunOutcome::Halted));
+  }
+
+  #[test]
+  fn test_replay_reports_first_difference() {
+    let (transcript, _) = record(Intcode8086::initialize(Input::inline(LESS_THAN_8).csv().unwrap()), &[9]);
+    let patched = LESS_THAN_8.replace("1101,1000,1", "1101,1000,2");
+
+    let divergence = replay(Input::inline(&patched).csv().unwrap(), &transcript).unwrap_err();
+
+    assert_eq!(divergence.index, 1);
+    assert_eq!(divergence.expected, Some(Event::Output { step: 7, value: 1001 }));
+    assert_eq!(divergence.actual, Some(Event::Output { step: 7, value: 1002 }));
+  }
+}
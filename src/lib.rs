@@ -0,0 +1,25 @@
+pub mod answers;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod fancyiters;
+pub mod grid;
+pub mod inputhandling;
+pub mod intcode;
+pub mod intcode_8086;
+pub mod intcode_cli;
+pub mod intcode_frames;
+pub mod intcode_screen;
+pub mod intcode_search;
+pub mod intcode_symbolic;
+pub mod intcode_transcript;
+pub mod pattern;
+pub mod runner;
+pub mod solution;
+pub mod timing;
@@ -1,24 +1,93 @@
-use std::error::Error;
+use adventofcode2019::{ answers, inputhandling, runner, solution, timing };
+use adventofcode2019::inputhandling::Input;
+use adventofcode2019::runner::Command;
+use adventofcode2019::solution::Entry;
 
-mod fancyiters;
-mod inputhandling;
-mod intcode_8086;
+fn verify(entries: &[Entry]) -> answers::Verification {
+  let answers = answers::Answers::load(answers::answers_path()).unwrap_or_else(|e| {
+    eprintln!("{}", e);
+    std::process::exit(2);
+  });
 
-fn main() -> Result<(), Box<dyn Error>> {
-  let vonNeumann : Vec<i64> = inputhandling::parse_csv_input(9, |s| s.parse::<i64>().map_err(|e| e.into()))?;
+  answers::verify(&answers, entries.iter().map(|e| e.day), |day, part| {
+    let entry = entries.iter().find(|e| e.day == day).ok_or_else(|| format!("Day {} has recorded answers but isn't registered", day))?;
+    Ok(entry.solve(&Input::for_day(day)?, part)?.to_string())
+  })
+}
 
-  let mut cpu = intcode_8086::Intcode8086::initialize(vonNeumann);
-  cpu.get_input_port().send(2).expect("Ceres coordinates");
-  let mut io = cpu.get_output_port();
+fn main() {
+  let args : Vec<String> = std::env::args().skip(1).collect();
+  let options = match runner::parse_args(&args) {
+    Ok(options) => options,
+    Err(e) => {
+      eprintln!("{}\n\n{}", e, runner::USAGE);
+      std::process::exit(2);
+    }
+  };
 
-  let handle = cpu.process();
+  if let Some(root) = &options.input_root {
+    inputhandling::set_input_root(root);
+  }
 
-  handle.join().expect("");
+  let entries = solution::registry();
 
-  for v in io.iter() {
-    println!("{}", v);
-  }
+  match options.command {
+    Command::Help => println!("{}", runner::USAGE),
+    Command::List { examples: false } => {
+      // Without an answers file every day is listed as unverified.
+      let answers = answers::Answers::load(answers::answers_path()).unwrap_or_default();
+      print!("{}", runner::format_list(&entries, &answers, options.json));
+    },
+    Command::List { examples: true } => print!("{}", runner::format_examples(&entries, options.json)),
+    Command::Verify => {
+      let verification = verify(&entries);
 
-  Ok(())
-}
+      println!("{}", verification);
+      if !verification.passed() {
+        std::process::exit(1);
+      }
+    },
+    Command::Time { selection, parts, source, repeats, export } => {
+      let timings = runner::time(&entries, &selection, &parts, &source, repeats).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+      });
+
+      if options.json {
+        println!("{}", timing::format_json(&timings));
+      } else {
+        print!("{}", timing::format_table(&timings));
+      }
 
+      if let Some(path) = export {
+        if let Err(e) = std::fs::write(&path, timing::format_json(&timings) + "\n") {
+          eprintln!("Couldn't write {}: {}", path.display(), e);
+          std::process::exit(2);
+        }
+      }
+
+      if timings.iter().any(|t| t.error.is_some()) {
+        std::process::exit(1);
+      }
+    },
+    Command::Run { selection, parts, source } => {
+      let reports = match runner::run(&entries, &selection, &parts, &source) {
+        Ok(reports) => reports,
+        Err(e) => {
+          eprintln!("{}", e);
+          std::process::exit(2);
+        }
+      };
+
+      if options.json {
+        println!("{}", runner::format_json(&reports));
+      } else {
+        print!("{}", runner::format_text(&reports));
+      }
+
+      if !reports.iter().all(|r| r.succeeded()) {
+        std::process::exit(1);
+      }
+    }
+  }
+}
@@ -0,0 +1,309 @@
+use std::error::Error;
+use std::str::FromStr;
+
+// A line format such as "{orbited}){orbiting}" or "{dir:char}{run:u32}". Text outside braces must
+// match literally ("{{" and "}}" stand for braces). A field's type decides how much it consumes:
+// `char` takes one character, integer types take a run of digits, and untyped (or `str`) fields
+// take everything up to the next literal.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Pattern {
+  source: String,
+  segments: Vec<Segment>
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+enum Segment {
+  Literal(String),
+  Field { name: String, kind: FieldKind }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum FieldKind {
+  Text,
+  Char,
+  Unsigned,
+  Signed
+}
+
+impl FieldKind {
+  fn parse(name: &str) -> Option<FieldKind> {
+    match name {
+      "" | "str" => Some(FieldKind::Text),
+      "char" => Some(FieldKind::Char),
+      "u8" | "u16" | "u32" | "u64" | "usize" => Some(FieldKind::Unsigned),
+      "i8" | "i16" | "i32" | "i64" | "isize" => Some(FieldKind::Signed),
+      _ => None
+    }
+  }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum PatternError {
+  Invalid { pattern: String, reason: String },
+  Mismatch { pattern: String, line: String, column: usize, reason: String }
+}
+
+impl std::fmt::Display for PatternError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      PatternError::Invalid { pattern, reason } => write!(f, "Invalid pattern '{}': {}", pattern, reason),
+      PatternError::Mismatch { pattern, line, column, reason } => write!(f, "'{}' doesn't match '{}' at column {}: {}", line, pattern, column, reason)
+    }
+  }
+}
+
+impl Error for PatternError {}
+
+// The raw text captured for each field, in pattern order.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Fields<'a> {
+  pattern: &'a Pattern,
+  line: &'a str,
+  values: Vec<(&'a str, &'a str, usize)>
+}
+
+impl<'a> Fields<'a> {
+  pub fn len(&self) -> usize {
+    self.values.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.values.is_empty()
+  }
+
+  pub fn text(&self, name: &str) -> Option<&'a str> {
+    self.values.iter().find(|(n, _, _)| *n == name).map(|(_, v, _)| *v)
+  }
+
+  pub fn get<T>(&self, index: usize) -> Result<T, PatternError>
+  where
+    T: FromStr,
+    T::Err: Into<Box<dyn Error>>,
+  {
+    let (name, value, column) = match self.values.get(index) {
+      Some(field) => *field,
+      None => return Err(self.pattern.mismatch(self.line, self.line.len() + 1, format!("There is no field {}", index)))
+    };
+
+    value.parse::<T>().map_err(|cause| {
+      let cause: Box<dyn Error> = cause.into();
+      self.pattern.mismatch(self.line, column, format!("Field '{}' can't be read from '{}': {}", name, value, cause))
+    })
+  }
+
+  pub fn field<T>(&self, name: &str) -> Result<T, PatternError>
+  where
+    T: FromStr,
+    T::Err: Into<Box<dyn Error>>,
+  {
+    match self.values.iter().position(|(n, _, _)| *n == name) {
+      Some(index) => self.get(index),
+      None => Err(self.pattern.mismatch(self.line, self.line.len() + 1, format!("There is no field named '{}'", name)))
+    }
+  }
+}
+
+// Anything that can be built from a line's fields. Tuples read their elements positionally.
+pub trait FromFields: Sized {
+  fn from_fields(fields: &Fields) -> Result<Self, PatternError>;
+}
+
+macro_rules! tuple_from_fields {
+  ($count:expr; $($t:ident $index:tt),+) => {
+    impl<$($t),+> FromFields for ($($t,)+)
+    where
+      $($t: FromStr, $t::Err: Into<Box<dyn Error>>),+
+    {
+      fn from_fields(fields: &Fields) -> Result<Self, PatternError> {
+        if fields.len() != $count {
+          let reason = format!("Expected {} fields but the pattern has {}", $count, fields.len());
+          return Err(PatternError::Invalid { pattern: fields.pattern.source.clone(), reason });
+        }
+
+        Ok(($(fields.get::<$t>($index)?,)+))
+      }
+    }
+  };
+}
+
+tuple_from_fields!(1; A 0);
+tuple_from_fields!(2; A 0, B 1);
+tuple_from_fields!(3; A 0, B 1, C 2);
+tuple_from_fields!(4; A 0, B 1, C 2, D 3);
+tuple_from_fields!(5; A 0, B 1, C 2, D 3, E 4);
+
+impl Pattern {
+  pub fn new(source: &str) -> Result<Pattern, PatternError> {
+    let invalid = |reason: String| PatternError::Invalid { pattern: source.to_string(), reason };
+
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+      match c {
+        '{' if chars.peek() == Some(&'{') => { chars.next(); literal.push('{'); },
+        '}' if chars.peek() == Some(&'}') => { chars.next(); literal.push('}'); },
+        '}' => return Err(invalid("Unmatched '}'".to_string())),
+        '{' => {
+          let mut field = String::new();
+          loop {
+            match chars.next() {
+              Some('}') => break,
+              Some(c) => field.push(c),
+              None => return Err(invalid("Unclosed '{'".to_string()))
+            }
+          }
+
+          let (name, kind_name) = match field.find(':') {
+            Some(index) => (field[..index].trim(), field[index + 1..].trim()),
+            None => (field.trim(), "")
+          };
+
+          let kind = FieldKind::parse(kind_name).ok_or_else(|| invalid(format!("Unknown field type '{}'", kind_name)))?;
+
+          if !literal.is_empty() {
+            segments.push(Segment::Literal(literal.split_off(0)));
+          }
+
+          if let Some(Segment::Field { name: previous, kind: FieldKind::Text }) = segments.last() {
+            return Err(invalid(format!("Text field '{}' must be followed by literal text, not field '{}'", previous, name)));
+          }
+
+          segments.push(Segment::Field { name: name.to_string(), kind });
+        },
+        c => literal.push(c)
+      }
+    }
+
+    if !literal.is_empty() {
+      segments.push(Segment::Literal(literal));
+    }
+
+    Ok(Pattern { source: source.to_string(), segments })
+  }
+
+  fn mismatch(&self, line: &str, column: usize, reason: String) -> PatternError {
+    PatternError::Mismatch { pattern: self.source.clone(), line: line.to_string(), column, reason }
+  }
+
+  pub fn fields<'a>(&'a self, line: &'a str) -> Result<Fields<'a>, PatternError> {
+    let mut values = Vec::new();
+    let mut position = 0;
+
+    for (index, segment) in self.segments.iter().enumerate() {
+      let rest = &line[position..];
+      let column = line[..position].chars().count() + 1;
+
+      match segment {
+        Segment::Literal(literal) => {
+          if !rest.starts_with(literal.as_str()) {
+            return Err(self.mismatch(line, column, format!("Expected '{}' but found '{}'", literal, rest)));
+          }
+
+          position += literal.len();
+        },
+        Segment::Field { name, kind } => {
+          let length = match kind {
+            FieldKind::Char => rest.chars().next().map(|c| c.len_utf8()).unwrap_or(0),
+            FieldKind::Unsigned => rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len()),
+            FieldKind::Signed => {
+              let sign = if rest.starts_with('-') || rest.starts_with('+') { 1 } else { 0 };
+              let digits = rest[sign..].find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len() - sign);
+              if digits == 0 { 0 } else { sign + digits }
+            },
+            FieldKind::Text => match self.segments.get(index + 1) {
+              Some(Segment::Literal(next)) => match rest.find(next.as_str()) {
+                Some(length) => length,
+                None => return Err(self.mismatch(line, column, format!("Expected '{}' after field '{}'", next, name)))
+              },
+              _ => rest.len()
+            }
+          };
+
+          if length == 0 {
+            return Err(self.mismatch(line, column, format!("Field '{}' is empty", name)));
+          }
+
+          values.push((name.as_str(), &rest[..length], column));
+          position += length;
+        }
+      }
+    }
+
+    if position < line.len() {
+      let column = line[..position].chars().count() + 1;
+      return Err(self.mismatch(line, column, format!("Unexpected trailing text '{}'", &line[position..])));
+    }
+
+    Ok(Fields { pattern: self, line, values })
+  }
+
+  pub fn parse<T: FromFields>(&self, line: &str) -> Result<T, PatternError> {
+    T::from_fields(&self.fields(line)?)
+  }
+}
+
+// One-off helper for parsers that match a single line against a fixed format.
+pub fn parse<T: FromFields>(pattern: &str, line: &str) -> Result<T, PatternError> {
+  Pattern::new(pattern)?.parse(line)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::inputhandling::Input;
+
+  #[test]
+  fn test_text_fields() {
+    let (a, b) : (String, String) = parse("{name}){name}", "COM)B").unwrap();
+
+    assert_eq!((a.as_str(), b.as_str()), ("COM", "B"));
+  }
+
+  #[test]
+  fn test_typed_fields() {
+    assert_eq!(parse::<(char, u32)>("{dir:char}{run:u32}", "R75"), Ok(('R', 75)));
+    assert_eq!(parse::<(i64, i64, i64)>("<x={x:i64}, y={y:i64}, z={z:i64}>", "<x=-1, y=0, z=+2>"), Ok((-1, 0, 2)));
+    assert_eq!(parse::<(String, u8)>("{{{key}}}: {value:u8}", "{a}: 7"), Ok(("a".to_string(), 7)));
+  }
+
+  #[test]
+  fn test_named_fields() {
+    let pattern = Pattern::new("{count:u32} {unit}").unwrap();
+    let fields = pattern.fields("12 ORE").unwrap();
+
+    assert_eq!(fields.text("unit"), Some("ORE"));
+    assert_eq!(fields.field::<u32>("count"), Ok(12));
+    assert!(fields.field::<u32>("missing").is_err());
+  }
+
+  #[test]
+  fn test_mismatches() {
+    let pattern = Pattern::new("{dir:char}{run:u32}").unwrap();
+
+    assert_eq!(pattern.parse::<(char, u32)>("R").unwrap_err().to_string(), "'R' doesn't match '{dir:char}{run:u32}' at column 2: Field 'run' is empty");
+    assert_eq!(pattern.parse::<(char, u32)>("R7x").unwrap_err().to_string(), "'R7x' doesn't match '{dir:char}{run:u32}' at column 3: Unexpected trailing text 'x'");
+    assert!(parse::<(String, String)>("{a}){b}", "COM-B").is_err());
+    assert!(parse::<(u8, u8)>("{a:u8},{b:u8}", "1,300").unwrap_err().to_string().contains("Field 'b' can't be read from '300'"));
+    assert!(pattern.parse::<(char, u32, u32)>("R7").is_err());
+  }
+
+  #[test]
+  fn test_invalid_patterns() {
+    assert!(Pattern::new("{a").is_err());
+    assert!(Pattern::new("a}").is_err());
+    assert!(Pattern::new("{a:float}").is_err());
+    assert!(Pattern::new("{a}{b}").is_err());
+    assert!(Pattern::new("{a:char}{b}").is_ok());
+  }
+
+  #[test]
+  fn test_with_input_lines() {
+    let pattern = Pattern::new("{a:u8}-{b:u8}").unwrap();
+    let err = Input::inline("1-2\n3+4").lines_with(|s| pattern.parse::<(u8, u8)>(s)).unwrap_err();
+
+    assert_eq!(err.line, 2);
+    assert!(err.to_string().contains("Expected '-' but found '+4'"));
+  }
+}
@@ -0,0 +1,521 @@
+use std::error::Error;
+use std::path::PathBuf;
+use super::answers::Answers;
+use super::inputhandling::{ self, ExpectedAnswers, Input };
+use super::solution::{ Answer, Entry };
+use super::timing::{ self, DayTiming, Stats };
+
+pub const USAGE: &str = "\
+usage: adventofcode2019 [--input-root <path>] [--json] <command>
+
+commands:
+  run --day <n> [--part <1|2>] [--input <path|->] [--example <name>]
+  run --all [--part <1|2>]
+  time [--day <n>] [--part <1|2>] [--repeat <n>] [--export <path>]
+  list [--examples]
+  verify";
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Selection {
+  All,
+  Day(u8)
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Source {
+  // The day's own input.txt under the input root.
+  Puzzle,
+  File(PathBuf),
+  Stdin,
+  Example(String)
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Command {
+  Run { selection: Selection, parts: Vec<u8>, source: Source },
+  // Times every registered day unless one is picked with --day; --export also writes JSON.
+  Time { selection: Selection, parts: Vec<u8>, source: Source, repeats: usize, export: Option<PathBuf> },
+  // With --examples, every day's example inputs and the parts they have answers for.
+  List { examples: bool },
+  Verify,
+  Help
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Options {
+  pub command: Command,
+  pub json: bool,
+  pub input_root: Option<PathBuf>
+}
+
+fn value<'a>(args: &'a [String], index: usize, flag: &str) -> Result<&'a str, String> {
+  args.get(index + 1).map(|s| s.as_str()).ok_or_else(|| format!("{} needs a value", flag))
+}
+
+// Flags may come before or after the command; anything unrecognised is an error.
+pub fn parse_args(args: &[String]) -> Result<Options, String> {
+  let mut command = None;
+  let mut json = false;
+  let mut input_root = None;
+  let mut day = None;
+  let mut all = false;
+  let mut parts = vec![1, 2];
+  let mut source = Source::Puzzle;
+  let mut repeats = 1;
+  let mut export = None;
+  let mut examples = false;
+
+  let mut index = 0;
+  while index < args.len() {
+    let arg = args[index].as_str();
+
+    match arg {
+      "--json" => json = true,
+      "--all" => all = true,
+      "--input-root" => { input_root = Some(PathBuf::from(value(args, index, arg)?)); index += 1; },
+      "--day" => {
+        day = Some(value(args, index, arg)?.parse::<u8>().map_err(|_| format!("--day needs a day number, not '{}'", args[index + 1]))?);
+        index += 1;
+      },
+      "--part" => {
+        parts = match value(args, index, arg)? {
+          "1" => vec![1],
+          "2" => vec![2],
+          other => return Err(format!("--part must be 1 or 2, not '{}'", other))
+        };
+        index += 1;
+      },
+      "--input" => {
+        source = match value(args, index, arg)? {
+          "-" => Source::Stdin,
+          path => Source::File(PathBuf::from(path))
+        };
+        index += 1;
+      },
+      "--repeat" => {
+        repeats = match value(args, index, arg)?.parse::<usize>() {
+          Ok(n) if n > 0 => n,
+          _ => return Err(format!("--repeat needs a positive count, not '{}'", args[index + 1]))
+        };
+        index += 1;
+      },
+      "--export" => { export = Some(PathBuf::from(value(args, index, arg)?)); index += 1; },
+      "--example" => { source = Source::Example(value(args, index, arg)?.to_string()); index += 1; },
+      "--examples" => examples = true,
+      "--verify" | "verify" => command = Some("verify"),
+      "run" | "time" | "list" | "help" | "--help" | "-h" => command = Some(arg),
+      other => return Err(format!("Unexpected argument '{}'", other))
+    }
+
+    index += 1;
+  }
+
+  let selection = match (day, all) {
+    (Some(day), false) => Some(Selection::Day(day)),
+    (None, true) => Some(Selection::All),
+    (Some(_), true) => return Err("Use either --day or --all, not both".to_string()),
+    (None, false) => None
+  };
+
+  if selection == Some(Selection::All) && source != Source::Puzzle {
+    return Err("--all always uses each day's own input".to_string());
+  }
+
+  let command = match command {
+    Some("run") => match selection {
+      Some(selection) => Command::Run { selection, parts, source },
+      None => return Err("run needs --day <n> or --all".to_string())
+    },
+    Some("time") => {
+      if selection.is_none() && source != Source::Puzzle {
+        return Err("--all always uses each day's own input".to_string());
+      }
+
+      Command::Time { selection: selection.unwrap_or(Selection::All), parts, source, repeats, export }
+    },
+    Some("list") => Command::List { examples },
+    Some("verify") => Command::Verify,
+    _ => Command::Help
+  };
+
+  Ok(Options { command, json, input_root })
+}
+
+pub struct PartReport {
+  pub part: u8,
+  pub answer: Result<Answer, String>,
+  // The example's recorded answer, when running an example that has one for this part.
+  pub expected: Option<String>
+}
+
+impl PartReport {
+  pub fn matches(&self) -> Option<bool> {
+    match (&self.answer, &self.expected) {
+      (Ok(answer), Some(expected)) => Some(answer.to_string() == *expected),
+      _ => None
+    }
+  }
+}
+
+// What running one day produced; a day whose input couldn't be read or parsed has an error
+// and no parts.
+pub struct DayReport {
+  pub day: u8,
+  pub title: &'static str,
+  pub error: Option<String>,
+  pub parts: Vec<PartReport>
+}
+
+impl DayReport {
+  pub fn succeeded(&self) -> bool {
+    self.error.is_none() && self.parts.iter().all(|p| p.answer.is_ok() && p.matches() != Some(false))
+  }
+}
+
+pub fn load(day: u8, source: &Source) -> Result<Input, Box<dyn Error>> {
+  let input = match source {
+    Source::Puzzle => Input::for_day(day)?,
+    Source::File(path) => Input::from_file(path)?,
+    Source::Stdin => Input::from_stdin()?,
+    Source::Example(name) => Input::example(day, name)?
+  };
+
+  Ok(input.with_day(day))
+}
+
+pub fn run_day(entry: &Entry, parts: &[u8], source: &Source) -> DayReport {
+  let runs = load(entry.day, source).and_then(|input| entry.run(&input, parts));
+  let expected = match source {
+    Source::Example(name) => inputhandling::example(entry.day, name).map(|e| e.expected).unwrap_or_default(),
+    _ => ExpectedAnswers::default()
+  };
+
+  let (error, parts) = match runs {
+    Ok(run) => (None, run.parts.into_iter().map(|r| PartReport {
+      part: r.part,
+      answer: r.answer.map_err(|e| e.to_string()),
+      expected: expected.part(r.part).map(|s| s.to_string())
+    }).collect()),
+    Err(e) => (Some(e.to_string()), Vec::new())
+  };
+
+  DayReport { day: entry.day, title: entry.title, error, parts }
+}
+
+// A day picked by --all whose input is missing still runs, and reports that as its error.
+pub fn select<'a>(entries: &'a [Entry], selection: &Selection) -> Result<Vec<&'a Entry>, String> {
+  match selection {
+    Selection::All => Ok(entries.iter().collect()),
+    Selection::Day(day) => match entries.iter().find(|e| e.day == *day) {
+      Some(entry) => Ok(vec![entry]),
+      None => Err(format!("Day {} isn't registered; `list` shows the days that are", day))
+    }
+  }
+}
+
+pub fn run(entries: &[Entry], selection: &Selection, parts: &[u8], source: &Source) -> Result<Vec<DayReport>, String> {
+  Ok(select(entries, selection)?.into_iter().map(|e| run_day(e, parts, source)).collect())
+}
+
+// Each day's input is read once, outside the timed runs.
+pub fn time(entries: &[Entry], selection: &Selection, parts: &[u8], source: &Source, repeats: usize) -> Result<Vec<DayTiming>, String> {
+  Ok(select(entries, selection)?.into_iter().map(|entry| match load(entry.day, source) {
+    Ok(input) => timing::time_day(entry, &input, parts, repeats),
+    Err(e) => DayTiming { day: entry.day, title: entry.title, parse: Stats::new(Vec::new()), parts: Vec::new(), error: Some(e.to_string()) }
+  }).collect())
+}
+
+pub fn format_text(reports: &[DayReport]) -> String {
+  let mut out = String::new();
+
+  for report in reports {
+    out.push_str(&format!("Day {}: {}\n", report.day, report.title));
+
+    if let Some(error) = &report.error {
+      out.push_str(&format!("  error: {}\n", error));
+    }
+
+    for part in &report.parts {
+      match &part.answer {
+        Ok(answer) => {
+          let answer = answer.to_string();
+          let expected = part.expected.as_deref().unwrap_or_default();
+          // A multi-line expected answer goes on lines of its own after the answer.
+          let expected_below = part.matches() == Some(false) && (expected.contains('\n') || answer.contains('\n'));
+          let check = match part.matches() {
+            Some(true) => " (pass)".to_string(),
+            Some(false) if expected_below => " (fail)".to_string(),
+            Some(false) => format!(" (fail, expected {})", expected),
+            None => String::new()
+          };
+
+          if answer.contains('\n') {
+            out.push_str(&format!("  part {}:{}\n", part.part, check));
+            for line in answer.lines() {
+              out.push_str(&format!("    {}\n", line));
+            }
+          } else {
+            out.push_str(&format!("  part {}: {}{}\n", part.part, answer, check));
+          }
+
+          if expected_below {
+            out.push_str("  expected:\n");
+            for line in expected.lines() {
+              out.push_str(&format!("    {}\n", line));
+            }
+          }
+        },
+        Err(e) => out.push_str(&format!("  part {} failed: {}\n", part.part, e))
+      }
+    }
+  }
+
+  out
+}
+
+pub fn json_string(value: &str) -> String {
+  let mut out = String::from("\"");
+
+  for c in value.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      '\r' => out.push_str("\\r"),
+      '\t' => out.push_str("\\t"),
+      c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+      c => out.push(c)
+    }
+  }
+
+  out.push('"');
+  out
+}
+
+fn json_answer(answer: &Answer) -> String {
+  match answer {
+    Answer::Number(n) => n.to_string(),
+    other => json_string(&other.to_string())
+  }
+}
+
+// One object per part, so the output can be filtered without knowing the nesting.
+pub fn format_json(reports: &[DayReport]) -> String {
+  let mut rows = Vec::new();
+
+  for report in reports {
+    let prefix = format!("\"day\": {}, \"title\": {}", report.day, json_string(report.title));
+
+    if let Some(error) = &report.error {
+      rows.push(format!("{{{}, \"error\": {}}}", prefix, json_string(error)));
+    }
+
+    for part in &report.parts {
+      match &part.answer {
+        Ok(answer) => {
+          let check = match (part.matches(), &part.expected) {
+            (Some(passed), Some(expected)) => format!(", \"expected\": {}, \"passed\": {}", json_string(expected), passed),
+            _ => String::new()
+          };
+
+          rows.push(format!("{{{}, \"part\": {}, \"answer\": {}{}}}", prefix, part.part, json_answer(answer), check))
+        },
+        Err(e) => rows.push(format!("{{{}, \"part\": {}, \"error\": {}}}", prefix, part.part, json_string(e)))
+      }
+    }
+  }
+
+  json_array(&rows)
+}
+
+pub fn json_array(rows: &[String]) -> String {
+  if rows.is_empty() {
+    return "[]".to_string();
+  }
+
+  format!("[\n  {}\n]", rows.join(",\n  "))
+}
+
+// Why a registered day can't be checked by verify: it has no puzzle input or nothing recorded
+// in the answers file.
+pub fn unverified_reasons(entry: &Entry, answers: &Answers) -> Vec<&'static str> {
+  let mut reasons = Vec::new();
+
+  if !inputhandling::input_path(entry.day).is_file() {
+    reasons.push("no input");
+  }
+
+  if [1, 2].iter().any(|part| answers.get(entry.day, *part).is_none()) {
+    reasons.push("no recorded answers");
+  }
+
+  reasons
+}
+
+pub fn format_list(entries: &[Entry], answers: &Answers, json: bool) -> String {
+  if json {
+    let rows = entries.iter().map(|e| {
+      let verified = unverified_reasons(e, answers).is_empty();
+      format!("{{\"day\": {}, \"title\": {}, \"verified\": {}}}", e.day, json_string(e.title), verified)
+    }).collect::<Vec<_>>();
+
+    return json_array(&rows);
+  }
+
+  entries.iter().map(|e| match unverified_reasons(e, answers).as_slice() {
+    [] => format!("{:>2}  {}\n", e.day, e.title),
+    reasons => format!("{:>2}  {}  (unverified: {})\n", e.day, e.title, reasons.join(", "))
+  }).collect()
+}
+
+// A day whose folder can't be read just has no examples.
+pub fn format_examples(entries: &[Entry], json: bool) -> String {
+  let examples = entries.iter().flat_map(|e| inputhandling::examples(e.day).unwrap_or_default()).collect::<Vec<_>>();
+
+  if json {
+    let rows = examples.iter().map(|example| {
+      let answer = |part| example.expected.part(part).map(json_string).unwrap_or_else(|| "null".to_string());
+      format!("{{\"day\": {}, \"example\": {}, \"part1\": {}, \"part2\": {}}}", example.day, json_string(&example.name), answer(1), answer(2))
+    }).collect::<Vec<_>>();
+
+    return json_array(&rows);
+  }
+
+  examples.iter().map(|example| {
+    let parts = [1, 2].iter().filter(|p| example.expected.part(**p).is_some()).map(|p| format!("part {}", p)).collect::<Vec<_>>();
+    let answers = if parts.is_empty() { "no answers".to_string() } else { parts.join(", ") };
+
+    format!("{:>2}  {}  {}\n", example.day, example.name, answers)
+  }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::answers::answers_path;
+  use super::super::solution::registry;
+
+  fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(|s| s.to_string()).collect()
+  }
+
+  #[test]
+  fn test_parse_args() {
+    let options = parse_args(&args("run --day 6 --part 2 --input in.txt --json")).unwrap();
+    assert_eq!(options.command, Command::Run { selection: Selection::Day(6), parts: vec![2], source: Source::File(PathBuf::from("in.txt")) });
+    assert!(options.json);
+
+    let options = parse_args(&args("--input-root /tmp/aoc run --all")).unwrap();
+    assert_eq!(options.command, Command::Run { selection: Selection::All, parts: vec![1, 2], source: Source::Puzzle });
+    assert_eq!(options.input_root, Some(PathBuf::from("/tmp/aoc")));
+
+    let options = parse_args(&args("time --repeat 5 --export timings.json")).unwrap();
+    assert_eq!(options.command, Command::Time { selection: Selection::All, parts: vec![1, 2], source: Source::Puzzle, repeats: 5, export: Some(PathBuf::from("timings.json")) });
+
+    assert_eq!(parse_args(&args("list")).unwrap().command, Command::List { examples: false });
+    assert_eq!(parse_args(&args("list --examples")).unwrap().command, Command::List { examples: true });
+    assert_eq!(parse_args(&args("--verify")).unwrap().command, Command::Verify);
+    assert_eq!(parse_args(&args("")).unwrap().command, Command::Help);
+  }
+
+  #[test]
+  fn test_parse_args_errors() {
+    assert!(parse_args(&args("run")).is_err());
+    assert!(parse_args(&args("run --day six")).is_err());
+    assert!(parse_args(&args("run --day 6 --part 3")).is_err());
+    assert!(parse_args(&args("run --all --day 6")).is_err());
+    assert!(parse_args(&args("run --all --example example1")).is_err());
+    assert!(parse_args(&args("run --day")).is_err());
+    assert!(parse_args(&args("time --repeat 0")).is_err());
+    assert!(parse_args(&args("time --example example1")).is_err());
+    assert!(parse_args(&args("frobnicate")).is_err());
+  }
+
+  #[test]
+  fn test_run_example() {
+    let reports = run(&registry(), &Selection::Day(6), &[1], &Source::Example("example1".to_string())).unwrap();
+
+    assert_eq!(format_text(&reports), "Day 6: Universal Orbit Map\n  part 1: 42 (pass)\n");
+    assert_eq!(format_json(&reports), "[\n  {\"day\": 6, \"title\": \"Universal Orbit Map\", \"part\": 1, \"answer\": 42, \"expected\": \"42\", \"passed\": true}\n]");
+    assert!(reports[0].succeeded());
+
+    let reports = run(&registry(), &Selection::Day(6), &[1], &Source::Puzzle).unwrap();
+    assert_eq!(reports[0].parts[0].expected, None);
+  }
+
+  #[test]
+  fn test_example_mismatch_fails() {
+    let report = DayReport {
+      day: 6,
+      title: "Universal Orbit Map",
+      error: None,
+      parts: vec![PartReport { part: 1, answer: Ok(Answer::Number(41)), expected: Some("42".to_string()) }]
+    };
+
+    assert!(!report.succeeded());
+    assert_eq!(format_text(&[report]), "Day 6: Universal Orbit Map\n  part 1: 41 (fail, expected 42)\n");
+  }
+
+  #[test]
+  fn test_format_examples() {
+    let entries = registry().into_iter().filter(|e| e.day == 6).collect::<Vec<_>>();
+
+    assert_eq!(format_examples(&entries, false), " 6  example1  part 1\n 6  example2  part 2\n");
+    assert_eq!(format_examples(&entries, true), [
+      "[",
+      "  {\"day\": 6, \"example\": \"example1\", \"part1\": \"42\", \"part2\": null},",
+      "  {\"day\": 6, \"example\": \"example2\", \"part1\": null, \"part2\": \"4\"}",
+      "]"
+    ].join("\n"));
+  }
+
+  #[test]
+  fn test_run_reports_errors() {
+    let reports = run(&registry(), &Selection::Day(6), &[2], &Source::Example("missing".to_string())).unwrap();
+    assert!(!reports[0].succeeded());
+    assert!(format_json(&reports).contains("\"error\": "));
+
+    let reports = run(&registry(), &Selection::Day(6), &[2], &Source::Example("example1".to_string())).unwrap();
+    assert_eq!(format_text(&reports), "Day 6: Universal Orbit Map\n  part 2 failed: The map needs both SAN and YOU to find a transfer\n");
+
+    assert!(run(&registry(), &Selection::Day(25), &[1], &Source::Puzzle).is_err());
+  }
+
+  #[test]
+  fn test_missing_input_is_reported() {
+    let days = select(&registry(), &Selection::All).unwrap().iter().map(|e| e.day).collect::<Vec<_>>();
+    assert!(days.contains(&4));
+
+    // Day 4's input isn't checked in.
+    let reports = run(&registry(), &Selection::Day(4), &[1, 2], &Source::Puzzle).unwrap();
+    assert!(!reports[0].succeeded());
+    assert!(format_text(&reports).contains(&inputhandling::input_path(4).display().to_string()));
+  }
+
+  #[test]
+  fn test_json_string() {
+    assert_eq!(json_string("a\"b\\c\nd\u{1}"), "\"a\\\"b\\\\c\\nd\\u0001\"");
+  }
+
+  #[test]
+  fn test_format_list() {
+    let answers = Answers::load(answers_path()).unwrap();
+    let entries = &registry()[..1];
+
+    assert_eq!(format_list(entries, &answers, false), " 1  The Tyranny of the Rocket Equation\n");
+    assert_eq!(format_list(entries, &answers, true), "[\n  {\"day\": 1, \"title\": \"The Tyranny of the Rocket Equation\", \"verified\": true}\n]");
+    assert_eq!(format_list(&registry()[..2], &answers, true).matches("},\n  {").count(), 1);
+    assert_eq!(format_list(&[], &answers, true), "[]");
+  }
+
+  #[test]
+  fn test_format_list_flags_unverified_days() {
+    let answers = Answers::load(answers_path()).unwrap();
+    let entries = registry().into_iter().filter(|e| e.day == 4).collect::<Vec<_>>();
+
+    // Day 4 has neither an input nor recorded answers in this checkout.
+    assert_eq!(format_list(&entries, &answers, false), " 4  Secure Container  (unverified: no input, no recorded answers)\n");
+    assert!(format_list(&entries, &answers, true).contains("\"verified\": false"));
+    assert_eq!(unverified_reasons(&entries[0], &Answers::parse("[day4]\npart1 = 1\npart2 = 2").unwrap()), vec!["no input"]);
+  }
+}
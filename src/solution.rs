@@ -0,0 +1,182 @@
+use std::error::Error;
+use std::time::{ Duration, Instant };
+use super::grid::Grid;
+use super::inputhandling::Input;
+use super::{ day1, day2, day3, day4, day5, day6, day7, day8, day9 };
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Answer {
+  Number(i64),
+  Text(String),
+  // Letters drawn on a grid, such as a decoded image; compared by how they render.
+  Image(Grid<char>)
+}
+
+impl std::fmt::Display for Answer {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Answer::Number(n) => write!(f, "{}", n),
+      Answer::Text(s) => write!(f, "{}", s),
+      Answer::Image(grid) => write!(f, "{}", grid.to_string().trim_end())
+    }
+  }
+}
+
+impl From<i64> for Answer {
+  fn from(n: i64) -> Answer {
+    Answer::Number(n)
+  }
+}
+
+impl From<u32> for Answer {
+  fn from(n: u32) -> Answer {
+    Answer::Number(n as i64)
+  }
+}
+
+impl From<usize> for Answer {
+  fn from(n: usize) -> Answer {
+    Answer::Number(n as i64)
+  }
+}
+
+impl From<String> for Answer {
+  fn from(s: String) -> Answer {
+    Answer::Text(s)
+  }
+}
+
+impl From<Grid<char>> for Answer {
+  fn from(grid: Grid<char>) -> Answer {
+    Answer::Image(grid)
+  }
+}
+
+// A day's puzzle: the input is parsed once and both parts work from the parsed form.
+pub trait Solution {
+  const DAY: u8;
+  const TITLE: &'static str;
+
+  type Parsed;
+
+  fn parse(input: &Input) -> Result<Self::Parsed, Box<dyn Error>>;
+  fn part1(parsed: &Self::Parsed) -> Result<Answer, Box<dyn Error>>;
+  fn part2(parsed: &Self::Parsed) -> Result<Answer, Box<dyn Error>>;
+}
+
+pub struct PartRun {
+  pub part: u8,
+  pub answer: Result<Answer, Box<dyn Error>>,
+  pub elapsed: Duration
+}
+
+pub struct Run {
+  pub parse_elapsed: Duration,
+  pub parts: Vec<PartRun>
+}
+
+type Runner = fn(&Input, &[u8]) -> Result<Run, Box<dyn Error>>;
+
+// A registered day, with its Solution erased so every day can sit in one list.
+#[derive(Clone, Copy)]
+pub struct Entry {
+  pub day: u8,
+  pub title: &'static str,
+  runner: Runner
+}
+
+fn run_solution<S: Solution>(input: &Input, parts: &[u8]) -> Result<Run, Box<dyn Error>> {
+  let start = Instant::now();
+  let parsed = S::parse(input)?;
+  let parse_elapsed = start.elapsed();
+
+  let parts = parts.iter().map(|part| {
+    let start = Instant::now();
+    let answer = match part {
+      1 => S::part1(&parsed),
+      2 => S::part2(&parsed),
+      other => Err(format!("Day {} has no part {}", S::DAY, other).into())
+    };
+
+    PartRun { part: *part, answer, elapsed: start.elapsed() }
+  }).collect();
+
+  Ok(Run { parse_elapsed, parts })
+}
+
+impl Entry {
+  pub fn of<S: Solution>() -> Entry {
+    Entry { day: S::DAY, title: S::TITLE, runner: run_solution::<S> }
+  }
+
+  // Fails only if the input can't be parsed; each part reports its own result.
+  pub fn run(&self, input: &Input, parts: &[u8]) -> Result<Run, Box<dyn Error>> {
+    (self.runner)(&input.clone().with_day(self.day), parts)
+  }
+
+  pub fn solve(&self, input: &Input, part: u8) -> Result<Answer, Box<dyn Error>> {
+    let mut run = self.run(input, &[part])?;
+    run.parts.remove(0).answer
+  }
+}
+
+// Every solved day, in day order. New days add themselves here.
+pub fn registry() -> Vec<Entry> {
+  vec![
+    Entry::of::<day1::RocketEquation>(),
+    Entry::of::<day2::ProgramAlarm>(),
+    Entry::of::<day3::CrossedWires>(),
+    Entry::of::<day4::SecureContainer>(),
+    Entry::of::<day5::SunnyWithAsteroids>(),
+    Entry::of::<day6::UniversalOrbitMap>(),
+    Entry::of::<day7::AmplificationCircuit>(),
+    Entry::of::<day8::SpaceImageFormat>(),
+    Entry::of::<day9::SensorBoost>()
+  ]
+}
+
+pub fn find(day: u8) -> Option<Entry> {
+  registry().into_iter().find(|e| e.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::inputhandling::examples;
+
+  #[test]
+  fn test_registry_is_in_day_order() {
+    let days = registry().iter().map(|e| e.day).collect::<Vec<_>>();
+    let mut sorted = days.clone();
+    sorted.sort();
+    sorted.dedup();
+
+    assert_eq!(days, sorted);
+    assert_eq!(find(6).map(|e| e.title), Some("Universal Orbit Map"));
+    assert!(find(25).is_none());
+  }
+
+  // Every example with recorded answers must be reproduced by its day's solution.
+  #[test]
+  fn test_examples_match_expected_answers() {
+    for entry in registry() {
+      for example in examples(entry.day).unwrap_or_default() {
+        for part in 1..=2 {
+          if let Some(expected) = example.expected.part(part) {
+            let answer = entry.solve(&example.input().unwrap(), part).unwrap();
+            assert_eq!(answer.to_string(), expected, "day {} {} part {}", entry.day, example.name, part);
+          }
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_answer_display() {
+    assert_eq!(Answer::from(42_u32).to_string(), "42");
+    assert_eq!(Answer::from("ABC".to_string()).to_string(), "ABC");
+
+    let image = Grid::from_rows(vec![vec!['#', '.'], vec!['.', '#']]).unwrap();
+    assert_eq!(Answer::from(image).to_string(), "#.\n.#");
+  }
+}
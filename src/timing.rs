@@ -0,0 +1,242 @@
+use std::time::Duration;
+use super::inputhandling::Input;
+use super::runner::{ json_array, json_string };
+use super::solution::Entry;
+
+// Every measurement of one step, across repeated runs.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Stats {
+  samples: Vec<Duration>
+}
+
+impl Stats {
+  pub fn new(mut samples: Vec<Duration>) -> Stats {
+    samples.sort();
+    Stats { samples }
+  }
+
+  pub fn runs(&self) -> usize {
+    self.samples.len()
+  }
+
+  pub fn min(&self) -> Duration {
+    self.samples.first().copied().unwrap_or_default()
+  }
+
+  pub fn median(&self) -> Duration {
+    let n = self.samples.len();
+
+    match n {
+      0 => Duration::default(),
+      n if n % 2 == 1 => self.samples[n / 2],
+      n => (self.samples[n / 2 - 1] + self.samples[n / 2]) / 2
+    }
+  }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct PartTiming {
+  pub part: u8,
+  pub stats: Stats
+}
+
+// How long a day took to parse and solve. A day that fails to parse or solve has an error
+// instead, since timing a failure says nothing useful.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct DayTiming {
+  pub day: u8,
+  pub title: &'static str,
+  pub parse: Stats,
+  pub parts: Vec<PartTiming>,
+  pub error: Option<String>
+}
+
+impl DayTiming {
+  pub fn part(&self, part: u8) -> Option<&Stats> {
+    self.parts.iter().find(|p| p.part == part).map(|p| &p.stats)
+  }
+
+  // The median of each step, added up.
+  pub fn total(&self) -> Duration {
+    self.parse.median() + self.parts.iter().map(|p| p.stats.median()).sum::<Duration>()
+  }
+}
+
+pub fn time_day(entry: &Entry, input: &Input, parts: &[u8], repeats: usize) -> DayTiming {
+  let mut parse = Vec::new();
+  let mut samples = vec![Vec::new(); parts.len()];
+  let mut error = None;
+
+  for _ in 0..repeats.max(1) {
+    match entry.run(input, parts) {
+      Ok(run) => {
+        parse.push(run.parse_elapsed);
+
+        for (index, part) in run.parts.into_iter().enumerate() {
+          match part.answer {
+            Ok(_) => samples[index].push(part.elapsed),
+            Err(e) => error = Some(format!("part {}: {}", part.part, e))
+          }
+        }
+      },
+      Err(e) => error = Some(e.to_string())
+    }
+
+    if error.is_some() {
+      break;
+    }
+  }
+
+  let parts = match error {
+    Some(_) => Vec::new(),
+    None => parts.iter().zip(samples).map(|(part, samples)| PartTiming { part: *part, stats: Stats::new(samples) }).collect()
+  };
+
+  DayTiming { day: entry.day, title: entry.title, parse: Stats::new(parse), parts, error }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+  let nanos = duration.as_nanos();
+
+  match nanos {
+    n if n < 1_000 => format!("{}ns", n),
+    n if n < 1_000_000 => format!("{:.1}µs", n as f64 / 1e3),
+    n if n < 1_000_000_000 => format!("{:.1}ms", n as f64 / 1e6),
+    n => format!("{:.2}s", n as f64 / 1e9)
+  }
+}
+
+fn cell(stats: Option<&Stats>) -> String {
+  match stats {
+    Some(stats) if stats.runs() > 1 => format!("{} ({})", format_duration(stats.median()), format_duration(stats.min())),
+    Some(stats) => format_duration(stats.median()),
+    None => "-".to_string()
+  }
+}
+
+// One row per day; with repeated runs each cell is "median (min)".
+pub fn format_table(timings: &[DayTiming]) -> String {
+  let mut rows = vec![["Day", "Title", "Parse", "Part 1", "Part 2", "Total"].iter().map(|s| s.to_string()).collect::<Vec<_>>()];
+
+  for timing in timings {
+    let mut row = vec![timing.day.to_string(), timing.title.to_string()];
+
+    match &timing.error {
+      Some(error) => row.push(format!("error: {}", error)),
+      None => {
+        row.push(cell(Some(&timing.parse)));
+        row.push(cell(timing.part(1)));
+        row.push(cell(timing.part(2)));
+        row.push(format_duration(timing.total()));
+      }
+    }
+
+    rows.push(row);
+  }
+
+  let widths = (0..6).map(|column| rows.iter().filter(|r| r.len() == 6).map(|r| r[column].chars().count()).max().unwrap_or(0)).collect::<Vec<_>>();
+
+  rows.iter().map(|row| {
+    let line = row.iter().enumerate().map(|(column, text)| {
+      // The error message is the last cell of its row, so it doesn't need padding.
+      if column == row.len() - 1 {
+        text.clone()
+      } else if column == 0 {
+        format!("{:>width$}", text, width = widths[column])
+      } else {
+        format!("{:<width$}", text, width = widths[column])
+      }
+    }).collect::<Vec<_>>().join("  ");
+
+    format!("{}\n", line)
+  }).collect()
+}
+
+fn json_stats(stats: &Stats) -> String {
+  format!("{{\"median_ns\": {}, \"min_ns\": {}, \"runs\": {}}}", stats.median().as_nanos(), stats.min().as_nanos(), stats.runs())
+}
+
+pub fn format_json(timings: &[DayTiming]) -> String {
+  let rows = timings.iter().map(|timing| {
+    let prefix = format!("\"day\": {}, \"title\": {}", timing.day, json_string(timing.title));
+
+    match &timing.error {
+      Some(error) => format!("{{{}, \"error\": {}}}", prefix, json_string(error)),
+      None => {
+        let parts = timing.parts.iter().map(|p| format!(", \"part{}\": {}", p.part, json_stats(&p.stats))).collect::<String>();
+        format!("{{{}, \"parse\": {}{}, \"total_ns\": {}}}", prefix, json_stats(&timing.parse), parts, timing.total().as_nanos())
+      }
+    }
+  }).collect::<Vec<_>>();
+
+  json_array(&rows)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::solution::find;
+
+  fn ms(values: &[u64]) -> Stats {
+    Stats::new(values.iter().map(|v| Duration::from_millis(*v)).collect())
+  }
+
+  #[test]
+  fn test_stats() {
+    assert_eq!(ms(&[5, 1, 3]).median(), Duration::from_millis(3));
+    assert_eq!(ms(&[5, 1, 3]).min(), Duration::from_millis(1));
+    assert_eq!(ms(&[4, 1, 2, 3]).median(), Duration::from_micros(2500));
+    assert_eq!(ms(&[]).median(), Duration::default());
+  }
+
+  #[test]
+  fn test_format_duration() {
+    assert_eq!(format_duration(Duration::from_nanos(999)), "999ns");
+    assert_eq!(format_duration(Duration::from_nanos(12_345)), "12.3µs");
+    assert_eq!(format_duration(Duration::from_micros(4_500)), "4.5ms");
+    assert_eq!(format_duration(Duration::from_millis(2_500)), "2.50s");
+  }
+
+  fn timing(error: Option<&str>) -> DayTiming {
+    DayTiming {
+      day: 6,
+      title: "Universal Orbit Map",
+      parse: ms(&[2, 1, 3]),
+      parts: if error.is_some() { Vec::new() } else { vec![PartTiming { part: 1, stats: ms(&[10, 12, 11]) }] },
+      error: error.map(|e| e.to_string())
+    }
+  }
+
+  #[test]
+  fn test_format_table() {
+    assert_eq!(format_table(&[timing(None), DayTiming { day: 12, ..timing(Some("no input")) }]), [
+      "Day  Title                Parse          Part 1           Part 2  Total",
+      "  6  Universal Orbit Map  2.0ms (1.0ms)  11.0ms (10.0ms)  -       13.0ms",
+      " 12  Universal Orbit Map  error: no input",
+      ""
+    ].join("\n"));
+  }
+
+  #[test]
+  fn test_format_json() {
+    assert_eq!(format_json(&[timing(None), timing(Some("no input"))]), [
+      "[",
+      "  {\"day\": 6, \"title\": \"Universal Orbit Map\", \"parse\": {\"median_ns\": 2000000, \"min_ns\": 1000000, \"runs\": 3}, \"part1\": {\"median_ns\": 11000000, \"min_ns\": 10000000, \"runs\": 3}, \"total_ns\": 13000000},",
+      "  {\"day\": 6, \"title\": \"Universal Orbit Map\", \"error\": \"no input\"}",
+      "]"
+    ].join("\n"));
+  }
+
+  #[test]
+  fn test_time_day() {
+    let entry = find(6).unwrap();
+
+    let timing = time_day(&entry, &Input::example(6, "example1").unwrap(), &[1], 3);
+    assert_eq!(timing.error, None);
+    assert_eq!(timing.parse.runs(), 3);
+    assert_eq!(timing.part(1).map(|s| s.runs()), Some(3));
+
+    let timing = time_day(&entry, &Input::example(6, "example1").unwrap(), &[1, 2], 3);
+    assert!(timing.error.unwrap().starts_with("part 2:"));
+  }
+}